@@ -1,4 +1,4 @@
-use std::sync::{Arc, OnceLock};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
 use std::sync::mpsc::{Sender, Receiver, channel};
 use std::task::{Waker, Context, Poll};
 
@@ -6,6 +6,25 @@ use crate::task::Task;
 
 pub(crate) static SPAWNER: OnceLock<Sender<Arc<Task>>> = OnceLock::new();
 
+/// The number of tasks sent to the worker which haven't been polled yet
+static QUEUED: (Mutex<usize>, Condvar) = (Mutex::new(0), Condvar::new());
+
+/// Send the task to the worker, and count it until it's polled
+pub(crate) fn send(sender: &Sender<Arc<Task>>, task: Arc<Task>) {
+    *QUEUED.0.lock().unwrap() += 1;
+    if sender.send(task).is_err() {
+        polled();
+    }
+}
+
+fn polled() {
+    let mut queued = QUEUED.0.lock().unwrap();
+    *queued -= 1;
+    if *queued == 0 {
+        QUEUED.1.notify_all();
+    }
+}
+
 struct Worker {
     tx: Sender<Arc<Task>>,
     rx: Receiver<Arc<Task>>,
@@ -27,13 +46,14 @@ impl Worker {
             }
 
             let mut empty = false;
-            if let Ok(future) = task.future.read() {
-                if future.is_none() {
-                    empty = true;
-                }
+            if let Ok(future) = task.future.read()
+            && future.is_none()
+            {
+                empty = true;
             }
 
             if empty { drop(task) }
+            polled();
         };
     }
 }
@@ -44,20 +64,32 @@ unsafe impl Sync for Worker {}
 pub struct Executor;
 
 impl Executor {
+    /// Spawn the worker thread. Calling this more than once is a no-op.
     pub fn init() {
-        let (tx, rx) = channel();
-        let worker = Worker { tx, rx };
+        SPAWNER.get_or_init(|| {
+            let (tx, rx) = channel();
+            let worker = Worker { tx, rx };
+            let spawner = worker.tx.clone();
 
-        SPAWNER.set(worker.tx.clone()).expect("Executor can only be initiated once");
+            let builder = std::thread::Builder::new().name("worker".to_string());
+            builder.spawn(move || worker.work()).unwrap();
 
-        let builder = std::thread::Builder::new().name("worker".to_string());
-        builder.spawn(move || worker.work()).unwrap();
+            spawner
+        });
     }
 
     pub fn spawn(future: impl Future<Output = ()> + 'static) {
         let spawner = SPAWNER.get().unwrap();
         let task = Arc::new(Task::new(spawner.clone(), future));
-        let _ = spawner.send(task);
+        send(spawner, task);
+    }
+
+    /// Block until the worker has polled every task sent to it, including the ones woken meanwhile.
+    /// A task which keeps waking itself, like [`sleep`](crate::sleep), keeps the worker busy
+    pub fn wait_idle() {
+        let (queued, idle) = &QUEUED;
+        let queued = queued.lock().unwrap();
+        drop(idle.wait_while(queued, |queued| *queued > 0).unwrap());
     }
 }
 
@@ -84,4 +116,41 @@ mod excutor_test {
             assert!(result.is_ok());
         });
     }
+
+    #[test]
+    fn init_twice() {
+        Executor::init();
+        Executor::init();
+
+        let (tx, rx) = std::sync::mpsc::channel();
+        Executor::spawn(async move { tx.send(()).unwrap() });
+        assert!(rx.recv_timeout(std::time::Duration::from_secs(5)).is_ok());
+    }
+
+    #[test]
+    fn wait_idle() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use crate::Channel;
+
+        Executor::init();
+
+        let (tx, mut rx) = Channel::new();
+        let received = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&received);
+        Executor::spawn(async move {
+            while rx.recv().await.is_some() {
+                counter.fetch_add(1, Ordering::SeqCst);
+            }
+        });
+
+        // the task waiting on the channel is woken by each notification
+        tx.notify();
+        Executor::wait_idle();
+        assert_eq!(received.load(Ordering::SeqCst), 1);
+
+        tx.notify();
+        Executor::wait_idle();
+        assert_eq!(received.load(Ordering::SeqCst), 2);
+    }
 }
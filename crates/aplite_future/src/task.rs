@@ -14,7 +14,7 @@ pub(crate) struct Task {
 impl Wake for Task {
     fn wake(self: Arc<Self>) {
         let task = Arc::clone(&self);
-        let _ = self.sender.send(task);
+        crate::executor::send(&self.sender, task);
     }
 }
 
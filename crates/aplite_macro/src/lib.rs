@@ -11,7 +11,6 @@
 /// let super_unique_id_name: SuperUniqueIdName = tree.create_entity();
 /// let another_id = AnotherId::new();
 /// ```
-
 #[macro_export]
 macro_rules! entity {
     { $vis:vis $name:ident } => {
@@ -35,18 +35,44 @@ impl Effect {
         Self::with_scope(Scope::new(tx), rx, f)
     }
 
-    pub fn with_scope<F, R>(scope: Scope, mut rx: Receiver, mut f: F) -> Self
+    /// Same as [`Effect::new`], but the first run happens right away on the current thread,
+    /// so its value is available before the executor gets to the effect
+    pub fn new_eager<F, R>(mut f: F) -> (Self, R)
+    where
+        F: FnMut(Option<R>) -> R + 'static,
+        R: Clone + 'static,
+    {
+        let (tx, rx) = Channel::new();
+        let scope = Arc::new(Scope::new(tx));
+
+        let prev_scope = Graph::set_scope(Some(Arc::clone(&scope).to_any_subscriber()));
+        let value = f(None);
+        Graph::set_scope(prev_scope);
+
+        let effect = Self::spawn(scope, rx, f, Some(value.clone()));
+        (effect, value)
+    }
+
+    pub fn with_scope<F, R>(scope: Scope, rx: Receiver, f: F) -> Self
     where
         F: FnMut(Option<R>) -> R + 'static,
         R: 'static,
     {
         scope.sender.notify();
-        let scope = Arc::new(scope);
+        Self::spawn(Arc::new(scope), rx, f, None)
+    }
+
+    /// Run `f` on the executor each time the scope is notified, starting from the `first` value
+    fn spawn<F, R>(scope: Arc<Scope>, mut rx: Receiver, mut f: F, first: Option<R>) -> Self
+    where
+        F: FnMut(Option<R>) -> R + 'static,
+        R: 'static,
+    {
         let node = Graph::insert(Arc::clone(&scope));
         let scope = scope.to_any_subscriber();
 
         Executor::spawn(async move {
-            let value = Arc::new(RwLock::new(None::<R>));
+            let value = Arc::new(RwLock::new(first));
 
             while rx.recv().await.is_some() {
                 #[cfg(test)] eprintln!("\n[NOTIFIED]      : {:?}", node);
@@ -68,6 +94,21 @@ impl Effect {
     }
 }
 
+impl Dispose for Effect {
+    /// The effect stops running, and the closure is dropped along with its pending task
+    fn dispose(&self) {
+        if let Some(any) = Graph::remove(&self.node)
+        && let Some(scope) = any.downcast_ref::<Arc<Scope>>()
+        {
+            scope.clear_source();
+        }
+    }
+
+    fn is_disposed(&self) -> bool {
+        Graph::is_removed(&self.node)
+    }
+}
+
 pub struct Scope {
     pub(crate) sender: Sender,
     pub(crate) source: RwLock<Vec<AnySource>>,
@@ -210,4 +251,57 @@ mod effect_test {
 
         std::thread::sleep(std::time::Duration::from_secs(10));
     }
+
+    #[test]
+    fn eager() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        Executor::init();
+
+        let (num, set_num) = Signal::split(1i32);
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&runs);
+
+        let (_effect, first) = Effect::new_eager(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            num.get() * 10
+        });
+        assert_eq!(first, 10);
+
+        // the executor doesn't run it again until the signal changes
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+
+        set_num.set(2);
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(runs.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn dispose() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        Executor::init();
+
+        let (num, set_num) = Signal::split(0i32);
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&runs);
+
+        let effect = Effect::new(move |_| {
+            num.get();
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        set_num.set(1);
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(runs.load(Ordering::Relaxed), 2);
+
+        effect.dispose();
+        assert!(effect.is_disposed());
+
+        set_num.set(2);
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        assert_eq!(runs.load(Ordering::Relaxed), 2);
+    }
 }
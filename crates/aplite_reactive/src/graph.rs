@@ -58,7 +58,7 @@ impl Graph {
             .get(&node.id)
             .and_then(|any| any.downcast_ref::<R>())
             .unwrap();
-        f(&r)
+        f(r)
     }
 
     pub(crate) fn try_with_downcast<R, F, U>(node: &Node<R>, f: F) -> Option<U>
@@ -113,10 +113,7 @@ pub(crate) struct Node<R> {
 
 impl<R> Clone for Node<R> {
     fn clone(&self) -> Self {
-        Self {
-            id: self.id,
-            marker: PhantomData,
-        }
+        *self
     }
 }
 
@@ -128,7 +125,7 @@ impl<R> PartialEq for Node<R> {
 
 impl<R> PartialOrd for Node<R> {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.id.partial_cmp(&other.id)
+        Some(self.cmp(other))
     }
 }
 
@@ -86,7 +86,7 @@ impl<T: Clone + 'static> Get for Signal<T> {
     }
 
     fn try_get_untracked(&self) -> Option<Self::Value> {
-        self.try_read(|n| n.map(Clone::clone))
+        self.try_read(|n| n.cloned())
     }
 }
 
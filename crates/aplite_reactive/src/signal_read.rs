@@ -60,7 +60,7 @@ impl<T: Clone + 'static> Get for SignalRead<T> {
     }
 
     fn try_get_untracked(&self) -> Option<Self::Value> {
-        self.try_read(|v| v.map(Clone::clone))
+        self.try_read(|v| v.cloned())
     }
 }
 
@@ -53,6 +53,8 @@ impl Atlas {
         }
         let size = Size::new(data.width as f32, data.height as f32);
         if let Some(id) = self.allocator.alloc(size) {
+            // registered right away, so the same image appended twice before the upload shares the slot
+            self.processed.insert(data.downgrade(), id);
             self.pending_data.insert(id, data);
 
             Some(id)
@@ -61,6 +63,18 @@ impl Atlas {
         }
     }
 
+    /// Free the slots of the images which have been dropped, so their space can be reused
+    pub(crate) fn collect_garbage(&mut self) {
+        self.processed.retain(|image, id| {
+            let alive = image.bytes.strong_count() > 0;
+            if !alive {
+                self.allocator.free(id);
+                self.pending_data.remove(id);
+            }
+            alive
+        });
+    }
+
     #[inline(always)]
     pub(crate) fn get_uv(&self, id: &AtlasId) -> Option<Rect> {
         self.allocator
@@ -79,6 +93,8 @@ impl Atlas {
     }
 
     pub(crate) fn update(&mut self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) {
+        self.collect_garbage();
+
         if !self.pending_data.is_empty() {
             for (id, data) in &self.pending_data {
                 let alignment = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
@@ -130,8 +146,6 @@ impl Atlas {
                         depth_or_array_layers: 1,
                     }
                 );
-
-                self.processed.insert(data.downgrade(), *id);
             }
 
             self.pending_data.clear();
@@ -175,6 +189,10 @@ struct AtlasAllocator {
     last_parent: Option<AtlasId>,
     allocated: IndexMap<AtlasId, Rect>,
 
+    // the size of the image currently stored in a slot, which may be smaller than the slot
+    used: Vec<Size>,
+    freed: Vec<AtlasId>,
+
     // tree
     parent: Vec<Option<AtlasId>>,
     first_child: Vec<Option<AtlasId>>,
@@ -187,6 +205,8 @@ impl AtlasAllocator {
             rect: Rect::from_size(size),
             last_parent: None,
             allocated: IndexMap::new(),
+            used: Vec::new(),
+            freed: Vec::new(),
             parent: Vec::new(),
             first_child: Vec::new(),
             next_sibling: Vec::new(),
@@ -194,11 +214,13 @@ impl AtlasAllocator {
     }
 
     fn alloc(&mut self, size: Size) -> Option<AtlasId> {
+        if let Some(id) = self.reuse(size) { return Some(id) }
         if size.area() > self.calculate_available_area() { return None };
 
         self.next_sibling.push(None);
         self.first_child.push(None);
         self.parent.push(None);
+        self.used.push(size);
 
         match self.last_parent {
             Some(last_parent) => {
@@ -236,10 +258,35 @@ impl AtlasAllocator {
         }
     }
 
+    /// Take the smallest freed slot which fits the size.
+    /// The slot keeps its place in the tree, so the packing of the other slots is unchanged
+    fn reuse(&mut self, size: Size) -> Option<AtlasId> {
+        let index = self.freed
+            .iter()
+            .enumerate()
+            .filter_map(|(i, id)| {
+                let rect = self.allocated.get(id)?;
+                (rect.width >= size.width && rect.height >= size.height).then_some((i, rect.area()))
+            })
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(i, _)| i)?;
+
+        let id = self.freed.swap_remove(index);
+        self.used[id.index()] = size;
+
+        Some(id)
+    }
+
+    fn free(&mut self, id: &AtlasId) {
+        if self.allocated.contains(id) && !self.freed.contains(id) {
+            self.freed.push(*id);
+        }
+    }
+
     fn scan(&self, size: Size) -> Option<(AtlasId, Vec2f)> {
         self.get_parents()
             .iter()
-            .find_map(|(id, rect)| self.identify_children(id, *rect, size))
+            .find_map(|(id, rect)| self.identify_children(id, rect, size))
     }
 
     fn identify_children(
@@ -254,8 +301,8 @@ impl AtlasAllocator {
                     .iter()
                     .find_map(|child_id| {
                         match self.get_last_child(child_id) {
-                            Some(last) => self.check_pos_for(last, child_id, size),
-                            None => self.check_pos_for(child_id, child_id, size),
+                            Some(last) => self.check_pos_for(last, child_id, rect, size),
+                            None => self.check_pos_for(child_id, child_id, rect, size),
                         }
                         .or(self.indentify_next_sibling(child_id, parent, size))
                     })
@@ -273,10 +320,11 @@ impl AtlasAllocator {
         &self,
         id: &AtlasId,
         parent: &AtlasId,
+        row: &Rect,
         size: Size,
     ) -> Option<(AtlasId, Vec2f)> {
         let rect = self.allocated.get(id).unwrap();
-        let cond1 = rect.max_y() + size.height <= rect.max_y();
+        let cond1 = rect.max_y() + size.height <= row.max_y();
         let cond2 = size.width <= rect.width;
 
         (cond1 && cond2).then_some((
@@ -371,8 +419,10 @@ impl AtlasAllocator {
         })
     }
 
-    fn get_pos(&self, id: &AtlasId) -> Option<&Rect> {
-        self.allocated.get(id)
+    fn get_pos(&self, id: &AtlasId) -> Option<Rect> {
+        self.allocated
+            .get(id)
+            .map(|rect| Rect::from_vec2f_size(rect.vec2f(), self.used[id.index()]))
     }
 
     // fn remove(&mut self, id: AtlasId) -> Option<Rect> {
@@ -390,6 +440,7 @@ impl AtlasAllocator {
     fn calculate_available_area(&self) -> f32 {
         let allocated = self.allocated
             .iter()
+            .filter(|(id, _)| !self.freed.contains(id))
             .fold(0.0,|sum, (_, rect)| {
                 sum + rect.area()
             });
@@ -432,4 +483,23 @@ mod atlas_test {
 
         eprintln!("{:#?}", allocator.allocated);
     }
+
+    #[test]
+    fn reuse_freed_slot() {
+        let mut allocator = AtlasAllocator::new(Size::new(100., 100.));
+
+        let first = allocator.alloc(Size::new(100., 50.)).unwrap();
+        let second = allocator.alloc(Size::new(100., 50.)).unwrap();
+        assert!(allocator.alloc(Size::new(10., 10.)).is_none());
+
+        // the freed slot takes a smaller image, at the same position
+        allocator.free(&first);
+        let third = allocator.alloc(Size::new(40., 40.)).unwrap();
+        assert_eq!(third, first);
+        assert_eq!(allocator.get_pos(&third), Some(Rect::new(0., 0., 40., 40.)));
+        assert_eq!(allocator.get_pos(&second), Some(Rect::new(0., 50., 100., 50.)));
+
+        allocator.free(&second);
+        assert!(allocator.alloc(Size::new(100., 60.)).is_none());
+    }
 }
@@ -0,0 +1,106 @@
+use aplite_types::{CornerRadius, Rect};
+
+/// A clip region in logical pixels, with the corner radii in logical pixels too
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Clip {
+    pub(crate) rect: Rect,
+    pub(crate) corners: CornerRadius,
+}
+
+impl Clip {
+    pub(crate) fn new(rect: Rect, corners: CornerRadius) -> Self {
+        // a radius can't be bigger than half of the shortest side
+        let max = rect.width.min(rect.height) / 2.0;
+        let corners = CornerRadius::new(
+            corners.tl.clamp(0.0, max),
+            corners.bl.clamp(0.0, max),
+            corners.br.clamp(0.0, max),
+            corners.tr.clamp(0.0, max),
+        );
+
+        Self { rect, corners }
+    }
+
+    /// The overlapping area. There's only one clip per element, so each corner is rounded
+    /// by the clips which have a corner at the same point, taking the largest radius
+    pub(crate) fn intersect(&self, other: &Self) -> Self {
+        let rect = self.rect.intersect(&other.rect);
+        let points = corner_points(&rect);
+
+        let radius = |i: usize| [self, other]
+            .iter()
+            .filter(|clip| corner_points(&clip.rect)[i] == points[i])
+            .map(|clip| radii(&clip.corners)[i])
+            .fold(0.0, f32::max);
+
+        Self::new(rect, CornerRadius::new(radius(0), radius(1), radius(2), radius(3)))
+    }
+
+    /// The center & the half size, and the corners, in physical pixels
+    pub(crate) fn physical(&self, scale_factor: f32) -> ([f32; 4], [f32; 4]) {
+        let rect = [
+            self.rect.center_x() * scale_factor,
+            self.rect.center_y() * scale_factor,
+            self.rect.width / 2.0 * scale_factor,
+            self.rect.height / 2.0 * scale_factor,
+        ];
+        let corners = [
+            self.corners.tl * scale_factor,
+            self.corners.bl * scale_factor,
+            self.corners.br * scale_factor,
+            self.corners.tr * scale_factor,
+        ];
+
+        (rect, corners)
+    }
+}
+
+/// The corners of the rect, in the same order as the fields of [`CornerRadius`]
+fn corner_points(rect: &Rect) -> [(f32, f32); 4] {
+    [
+        (rect.x, rect.y),
+        (rect.x, rect.max_y()),
+        (rect.max_x(), rect.max_y()),
+        (rect.max_x(), rect.y),
+    ]
+}
+
+fn radii(corners: &CornerRadius) -> [f32; 4] {
+    [corners.tl, corners.bl, corners.br, corners.tr]
+}
+
+#[cfg(test)]
+mod clip_test {
+    use super::*;
+
+    #[test]
+    fn nested_clips() {
+        let outer = Clip::new(Rect::new(0.0, 0.0, 100.0, 100.0), CornerRadius::splat(80.0));
+        assert_eq!(outer.corners.tl, 50.0);
+
+        // the inner clip keeps its own corners
+        let inner = Clip::new(Rect::new(10.0, 10.0, 20.0, 20.0), CornerRadius::splat(4.0));
+        assert_eq!(outer.intersect(&inner), inner);
+
+        // partially overlapping clips keep only the corners they share with the overlap
+        let partial = Clip::new(Rect::new(50.0, 50.0, 100.0, 100.0), CornerRadius::splat(4.0));
+        let clip = outer.intersect(&partial);
+        assert_eq!(clip.rect, Rect::new(50.0, 50.0, 50.0, 50.0));
+        assert_eq!(clip.corners, CornerRadius::new(4.0, 0.0, 25.0, 0.0));
+
+        // a square clip filling a rounded one keeps the rounded corners
+        let square = Clip::new(outer.rect, CornerRadius::splat(0.0));
+        assert_eq!(outer.intersect(&square), outer);
+        assert_eq!(square.intersect(&outer), outer);
+
+        // the containing clip rounds the corners which coincide
+        let top_left = Clip::new(Rect::new(0.0, 0.0, 40.0, 40.0), CornerRadius::splat(4.0));
+        let clip = outer.intersect(&top_left);
+        assert_eq!(clip.rect, top_left.rect);
+        assert_eq!(clip.corners, CornerRadius::new(20.0, 4.0, 4.0, 4.0));
+
+        let (rect, corners) = inner.physical(2.0);
+        assert_eq!(rect, [40.0, 40.0, 20.0, 20.0]);
+        assert_eq!(corners, [8.0; 4]);
+    }
+}
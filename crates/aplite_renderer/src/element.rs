@@ -6,6 +6,10 @@ pub struct Element {
     pub(crate) background: Rgba<f32>,
     pub(crate) border: Rgba<f32>,
     pub(crate) corners: CornerRadius,
+    /// The active clip in physical pixels, as center (xy) & half size (zw)
+    pub(crate) clip: [f32; 4],
+    /// The corner radii of the clip in physical pixels, in the same order as [`CornerRadius`]
+    pub(crate) clip_corners: [f32; 4],
     pub(crate) shape: Shape,
     pub(crate) border_width: f32,
     pub(crate) atlas_id: i32,
@@ -20,12 +24,20 @@ pub enum Shape {
     Triangle = 3,
 }
 
+impl Default for Element {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Element {
     pub const fn new() -> Self {
         Self {
             background: Rgba::new(1., 0., 0., 1.),
             border: Rgba::new(1., 1., 1., 1.),
             corners: CornerRadius::splat(25.),
+            clip: [0.0, 0.0, f32::MAX, f32::MAX],
+            clip_corners: [0.0; 4],
             shape: Shape::RoundedRect,
             border_width: 0.0,
             atlas_id: -1,
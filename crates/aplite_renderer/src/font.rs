@@ -0,0 +1,209 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use aplite_types::{ImageData, Rect, Size};
+
+#[derive(Debug)]
+pub enum FontError {
+    Io(std::io::Error),
+    Parse(&'static str),
+}
+
+impl std::fmt::Display for FontError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "{err}"),
+            Self::Parse(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl std::error::Error for FontError {}
+
+impl From<std::io::Error> for FontError {
+    fn from(value: std::io::Error) -> Self {
+        Self::Io(value)
+    }
+}
+
+/// A TTF/OTF font. Cloning is cheap, and the clones share the rasterized glyphs
+#[derive(Clone)]
+pub struct Font {
+    inner: Arc<fontdue::Font>,
+    glyphs: Arc<Mutex<HashMap<(char, u32), Glyph>>>,
+}
+
+/// A rasterized glyph, positioned relative to the top left corner of the text
+pub struct GlyphQuad {
+    pub rect: Rect,
+    pub image: ImageData,
+}
+
+struct Glyph {
+    image: Option<ImageData>,
+    xmin: f32,
+    ymin: f32,
+    advance: f32,
+}
+
+impl Font {
+    pub fn from_bytes(bytes: impl Into<Vec<u8>>) -> Result<Self, FontError> {
+        let font = fontdue::Font::from_bytes(bytes.into(), fontdue::FontSettings::default())
+            .map_err(FontError::Parse)?;
+
+        Ok(Self {
+            inner: Arc::new(font),
+            glyphs: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self, FontError> {
+        let bytes = std::fs::read(path)?;
+        Self::from_bytes(bytes)
+    }
+
+    /// The distance between two baselines
+    pub fn line_height(&self, font_size: f32) -> f32 {
+        self.line_metrics(font_size).1
+    }
+
+    /// The bounding box of the laid out `text`
+    pub fn measure(&self, text: &str, font_size: f32) -> Size {
+        self.walk(text, font_size, |_, _, _| {})
+    }
+
+    /// Rasterize every visible glyph of `text`, one quad per glyph.
+    /// The glyph images are cached, so the same [`ImageData`] is returned for the same glyph.
+    pub fn layout(&self, text: &str, font_size: f32) -> Vec<GlyphQuad> {
+        let mut quads = Vec::with_capacity(text.len());
+
+        self.walk(text, font_size, |x, baseline, glyph| {
+            if let Some(image) = glyph.image.as_ref() {
+                let rect = Rect::new(
+                    (x + glyph.xmin).round(),
+                    (baseline - glyph.ymin - image.height as f32).round(),
+                    image.width as f32,
+                    image.height as f32,
+                );
+                quads.push(GlyphQuad { rect, image: image.clone() });
+            }
+        });
+
+        quads
+    }
+
+    fn line_metrics(&self, font_size: f32) -> (f32, f32) {
+        self.inner
+            .horizontal_line_metrics(font_size)
+            .map(|m| (m.ascent, m.new_line_size))
+            .unwrap_or((font_size, font_size))
+    }
+
+    /// Walk through the chars of `text`, calling `f` with the pen x, the baseline, and the glyph.
+    /// Returns the size of the whole text.
+    fn walk(&self, text: &str, font_size: f32, mut f: impl FnMut(f32, f32, &Glyph)) -> Size {
+        let (ascent, line_height) = self.line_metrics(font_size);
+        let mut glyphs = self.glyphs.lock().unwrap();
+
+        let mut pen_x = 0.0f32;
+        let mut line_top = 0.0;
+        let mut width = 0.0f32;
+        let mut prev = None;
+
+        for c in text.chars() {
+            if c == '\n' {
+                width = width.max(pen_x);
+                pen_x = 0.0;
+                line_top += line_height;
+                prev = None;
+                continue;
+            }
+
+            if let Some(left) = prev
+            && let Some(kern) = self.inner.horizontal_kern(left, c, font_size)
+            {
+                pen_x += kern;
+            }
+
+            let glyph = glyphs
+                .entry((c, font_size.to_bits()))
+                .or_insert_with(|| rasterize(&self.inner, c, font_size));
+
+            f(pen_x, line_top + ascent, glyph);
+
+            pen_x += glyph.advance;
+            prev = Some(c);
+        }
+
+        Size::new(width.max(pen_x), line_top + line_height)
+    }
+}
+
+impl std::fmt::Debug for Font {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.inner.name().unwrap_or("Font"))
+    }
+}
+
+impl PartialEq for Font {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+/// The glyph coverage is stored in the alpha channel, so the shader can tint it with the text color.
+/// There's one transparent texel around the glyph to avoid sampling the neighbours in the atlas.
+fn rasterize(font: &fontdue::Font, c: char, font_size: f32) -> Glyph {
+    let (metrics, coverage) = font.rasterize(c, font_size);
+
+    let image = (metrics.width > 0 && metrics.height > 0).then(|| {
+        let width = metrics.width + 2;
+        let height = metrics.height + 2;
+        let mut bytes = [255, 255, 255, 0].repeat(width * height);
+
+        for y in 0..metrics.height {
+            for x in 0..metrics.width {
+                let i = ((y + 1) * width + x + 1) * 4;
+                bytes[i + 3] = coverage[y * metrics.width + x];
+            }
+        }
+
+        ImageData::new((width as u32, height as u32), &bytes)
+    });
+
+    Glyph {
+        image,
+        xmin: metrics.xmin as f32 - 1.0,
+        ymin: metrics.ymin as f32 - 1.0,
+        advance: metrics.advance_width,
+    }
+}
+
+#[cfg(test)]
+mod font_test {
+    use super::*;
+
+    /// DejaVu Sans with only the ASCII glyphs, see `assets/LICENSE-DejaVu`
+    const FONT: &[u8] = include_bytes!("../assets/DejaVuSans-Ascii.ttf");
+
+    #[test]
+    fn layout() {
+        let font = Font::from_bytes(FONT).expect("the test font should load");
+
+        let size = font.measure("hello\nworld!", 16.0);
+        assert_eq!(size.height, font.line_height(16.0) * 2.0);
+        assert!(size.width > font.measure("hello", 16.0).width);
+
+        // space has no bitmap, and the same glyph is rasterized only once
+        let quads = font.layout("l l", 16.0);
+        assert_eq!(quads.len(), 2);
+        assert!(quads[0].image == quads[1].image);
+        assert!(quads[1].rect.x > quads[0].rect.x);
+    }
+
+    #[test]
+    fn invalid_bytes() {
+        assert!(matches!(Font::from_bytes(vec![0u8; 16]), Err(FontError::Parse(_))));
+    }
+}
@@ -1,5 +1,6 @@
 mod atlas;
 mod buffer;
+mod clip;
 mod shader;
 mod util;
 mod element;
@@ -7,10 +8,12 @@ mod storage;
 mod screen;
 mod renderer;
 mod mesh;
+mod font;
 
 pub use renderer::{Renderer, Scene};
 pub use element::{Element, Shape};
 pub use mesh::Vertices;
+pub use font::{Font, FontError, GlyphQuad};
 
 #[derive(Debug)]
 pub enum InitiationError {
@@ -96,6 +96,12 @@ impl std::ops::DerefMut for Indices {
     }
 }
 
+impl Default for Vertices {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Vertices {
     const VERTICES: Self = Self ([
         Vertex { _pos: Vec2f::new(-1.0,  1.0), uv: Vec2f::new(0.0, 0.0), id: 0 },
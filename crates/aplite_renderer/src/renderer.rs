@@ -1,7 +1,9 @@
 use std::sync::Arc;
 use winit::window::Window;
 use winit::dpi::PhysicalSize;
-use aplite_types::{Matrix3x2, Rgba, Size, PaintRef};
+use aplite_types::{CornerRadius, Matrix3x2, Rect, Rgba, Size, PaintRef};
+
+use crate::clip::Clip;
 
 use super::RenderError;
 use super::InitiationError;
@@ -170,12 +172,14 @@ impl Renderer {
     pub fn new_scene(&mut self) -> Scene<'_> {
         Scene {
             screen_res: self.screen_res(),
+            scale_factor: self.scale_factor() as f32,
             device: &self.device,
             queue: &self.queue,
             storage: &mut self.storage[self.current],
             mesh: &mut self.mesh[self.current],
             atlas: &mut self.atlas,
             clear_color: &mut self.clear_color,
+            clips: Vec::new(),
         }
     }
 
@@ -259,7 +263,9 @@ pub struct Scene<'a> {
     mesh: &'a mut MeshBuffer,
     atlas: &'a mut Atlas,
     screen_res: Size,
+    scale_factor: f32,
     clear_color: &'a mut Rgba<f32>,
+    clips: Vec<Clip>,
 }
 
 // FIXME: this feels immediate mode to me, idk
@@ -271,6 +277,7 @@ impl Scene<'_> {
         border: PaintRef<'_>,
         border_width: f32,
         shape: Shape,
+        corners: CornerRadius,
     ) {
         use aplite_storage::Entity;
 
@@ -278,13 +285,18 @@ impl Scene<'_> {
 
         let mut element = Element::new()
             .with_shape(shape)
+            .with_corner_radius(corners)
             .with_border_width(border_width);
 
+        if let Some(clip) = self.clips.last() {
+            (element.clip, element.clip_corners) = clip.physical(self.scale_factor);
+        }
+
         match border {
             PaintRef::Color(rgba) => {
                 element.border = rgba.f32();
             },
-            PaintRef::Image(_image_ref) => {},
+            PaintRef::Image(_) | PaintRef::Glyph(..) => {},
         }
 
         let atlas_id = match background {
@@ -292,9 +304,16 @@ impl Scene<'_> {
                 element.background = rgba.f32();
                 None
             },
-            PaintRef::Image(image_ref) => image_ref
-                .upgrade()
-                .and_then(|image| self.atlas.append(image)),
+            PaintRef::Image(image_ref) => {
+                element.background = Rgba::WHITE.f32();
+                image_ref
+                    .upgrade()
+                    .and_then(|image| self.atlas.append(image))
+            },
+            PaintRef::Glyph(mask, rgba) => {
+                element.background = rgba.f32();
+                mask.upgrade().and_then(|mask| self.atlas.append(mask))
+            },
         };
 
         let indices = Indices::new().with_offset(offset as _, true);
@@ -327,6 +346,26 @@ impl Scene<'_> {
         self.mesh.offset += 1;
     }
 
+    /// Clip everything drawn until the matching [`Scene::pop_clip`] to `rect`, in logical pixels.
+    /// Nested clips are intersected with the current one.
+    pub fn push_clip(&mut self, rect: Rect) {
+        self.push_rounded_clip(rect, CornerRadius::splat(0.0));
+    }
+
+    /// Same as [`Scene::push_clip`], with the corners rounded by the radii in logical pixels
+    pub fn push_rounded_clip(&mut self, rect: Rect, corners: CornerRadius) {
+        let clip = Clip::new(rect, corners);
+        let clip = match self.clips.last() {
+            Some(current) => current.intersect(&clip),
+            None => clip,
+        };
+        self.clips.push(clip);
+    }
+
+    pub fn pop_clip(&mut self) {
+        self.clips.pop();
+    }
+
     pub fn size(&self) -> Size {
         self.screen_res
     }
@@ -30,6 +30,8 @@ struct Element {
     background: vec4<f32>,
     border: vec4<f32>,
     radius: Radius,
+    clip: vec4<f32>,
+    clip_corners: vec4<f32>,
     shape: u32,
     border_width: f32,
     atlas_id: i32,
@@ -136,17 +138,30 @@ pub const FRAGMENT: &str = r"
 @group(2) @binding(0) var t: texture_2d<f32>;
 @group(3) @binding(0) var s: sampler;
 
+// 1.0 inside the clip, 0.0 outside, antialiased on the rounded corners.
+// everything is in physical pixels, with y pointing down
+fn clip_mask(pos: vec2<f32>, clip: vec4<f32>, corners: vec4<f32>) -> f32 {
+    let p = pos - clip.xy;
+    // (top_left, bot_left) on the left side, (top_right, bot_right) on the right
+    let side = select(corners.xy, corners.wz, p.x > 0.0);
+    let r = select(side.x, side.y, p.y > 0.0);
+    let d = abs(p) - clip.zw + r;
+    let sdf = min(max(d.x, d.y), 0.0) + length(max(d, vec2f(0.0))) - r;
+    return clamp(0.5 - sdf, 0.0, 1.0);
+}
+
 @fragment
 fn fs_main(in: FragmentPayload) -> @location(0) vec4<f32> {
     let element = elements[in.index];
+    let mask = vec4f(1.0, 1.0, 1.0, clip_mask(in.position.xy, element.clip, element.clip_corners));
 
-    if element.atlas_id > -1 { return textureSample(t, s, in.uv); }
+    if element.atlas_id > -1 { return textureSample(t, s, in.uv) * element.background * mask; }
 
     let sdf = sdf(in.uv, in.index, element);
     let blend = 1.0 - smoothstep(0.0, element.border_width, abs(sdf));
 
     let color = select(vec4f(0.0), element.background, sdf < 0.0);
-    return mix(color, element.border, blend);
+    return mix(color, element.border, blend) * mask;
 }
 ";
 
@@ -155,3 +170,26 @@ fn fs_main(in: FragmentPayload) -> @location(0) vec4<f32> {
 // fn compute(@builtin(global_invocation_id) id: vec3u) {
 // }
 // ";
+
+#[cfg(test)]
+mod shader_test {
+    use wgpu::naga;
+
+    #[test]
+    fn render_shader_is_valid() {
+        let module = naga::front::wgsl::parse_str(&super::render()).unwrap();
+        let mut validator = naga::valid::Validator::new(
+            naga::valid::ValidationFlags::all(),
+            naga::valid::Capabilities::empty(),
+        );
+        validator.validate(&module).unwrap();
+
+        // the storage buffer layout must match the rust side
+        let element = module.types
+            .iter()
+            .find(|(_, ty)| ty.name.as_deref() == Some("Element"))
+            .map(|(_, ty)| ty.inner.size(module.to_ctx()))
+            .unwrap();
+        assert_eq!(element as usize, size_of::<crate::element::Element>());
+    }
+}
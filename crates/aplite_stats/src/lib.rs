@@ -6,6 +6,12 @@ pub struct Stats {
     shortest: std::time::Duration,
 }
 
+impl Default for Stats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Stats {
     pub fn new() -> Self {
         Self {
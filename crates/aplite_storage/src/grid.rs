@@ -0,0 +1,145 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use aplite_types::{Rect, Vec2f};
+
+use crate::entity::Entity;
+use crate::hash::U64Map;
+
+/// The first & last cell covered by a bounding box, inclusive
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct CellRange {
+    min: (i32, i32),
+    max: (i32, i32),
+}
+
+impl CellRange {
+    fn cells(self) -> impl Iterator<Item = (i32, i32)> {
+        (self.min.1..=self.max.1)
+            .flat_map(move |y| (self.min.0..=self.max.0).map(move |x| (x, y)))
+    }
+}
+
+/// A uniform grid over the bounding boxes of the entities.
+/// A query only returns the entities sharing the cell of the position,
+/// so the caller still needs to do the exact test on each of them.
+pub struct SpatialGrid<E> {
+    cell_size: f32,
+    cells: HashMap<(i32, i32), Vec<E>>,
+    bounds: U64Map<E, CellRange>,
+}
+
+impl<E: Entity + Hash + Eq> SpatialGrid<E> {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            bounds: U64Map::new(),
+        }
+    }
+
+    fn cell_of(&self, pos: Vec2f) -> (i32, i32) {
+        (
+            (pos.x / self.cell_size).floor() as i32,
+            (pos.y / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn range_of(&self, rect: Rect) -> CellRange {
+        CellRange {
+            min: self.cell_of(Vec2f::new(rect.x, rect.y)),
+            max: self.cell_of(Vec2f::new(rect.max_x(), rect.max_y())),
+        }
+    }
+
+    /// Insert the entity, or move it if it's already inside.
+    /// Nothing is done if the entity still covers the same cells
+    pub fn insert(&mut self, entity: E, bounds: Rect) {
+        let range = self.range_of(bounds);
+        match self.bounds.get(&entity) {
+            Some(prev) if *prev == range => return,
+            Some(_) => self.remove(&entity),
+            None => {},
+        }
+
+        range.cells().for_each(|cell| self.cells.entry(cell).or_default().push(entity));
+        self.bounds.insert(entity, range);
+    }
+
+    pub fn remove(&mut self, entity: &E) {
+        let Some(range) = self.bounds.remove(entity) else { return };
+
+        range.cells().for_each(|cell| {
+            if let Some(entities) = self.cells.get_mut(&cell) {
+                entities.retain(|e| e != entity);
+                if entities.is_empty() {
+                    self.cells.remove(&cell);
+                }
+            }
+        });
+    }
+
+    pub fn retain(&mut self, mut f: impl FnMut(&E) -> bool) {
+        let removed = self.bounds
+            .iter()
+            .filter_map(|(entity, _)| (!f(entity)).then_some(*entity))
+            .collect::<Vec<_>>();
+        removed.iter().for_each(|entity| self.remove(entity));
+    }
+
+    /// The entities whose bounding box may contain the position
+    pub fn query(&self, pos: Vec2f) -> &[E] {
+        self.cells
+            .get(&self.cell_of(pos))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn contains(&self, entity: &E) -> bool {
+        self.bounds.contains_key(entity)
+    }
+
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bounds.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.bounds.clear();
+    }
+}
+
+#[cfg(test)]
+mod grid_test {
+    use super::*;
+    use crate::create_entity;
+
+    create_entity! { TestId }
+
+    #[test]
+    fn query_moved_and_removed() {
+        let mut grid = SpatialGrid::new(10.0);
+        let a = TestId::new(0, 0);
+        let b = TestId::new(1, 0);
+
+        grid.insert(a, Rect::new(0., 0., 25., 5.));
+        grid.insert(b, Rect::new(15., 0., 10., 10.));
+        assert_eq!(grid.query(Vec2f::new(5., 5.)), &[a]);
+        assert_eq!(grid.query(Vec2f::new(21., 5.)), &[a, b]);
+        assert!(grid.query(Vec2f::new(5., 50.)).is_empty());
+
+        grid.insert(a, Rect::new(0., 40., 5., 15.));
+        assert!(grid.query(Vec2f::new(5., 5.)).is_empty());
+        assert_eq!(grid.query(Vec2f::new(5., 50.)), &[a]);
+        assert_eq!(grid.query(Vec2f::new(21., 5.)), &[b]);
+
+        grid.remove(&a);
+        assert!(!grid.contains(&a));
+        assert!(grid.query(Vec2f::new(5., 50.)).is_empty());
+        assert_eq!(grid.len(), 1);
+    }
+}
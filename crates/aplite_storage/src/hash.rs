@@ -22,10 +22,6 @@ impl<K, V> U64Map<K, V> {
         self.0.iter()
     }
 
-    pub fn into_iter(self) -> IntoIter<K, V> {
-        self.0.into_iter()
-    }
-
     pub fn iter_mut(&mut self) -> IterMut<'_, K, V> {
         self.0.iter_mut()
     }
@@ -72,6 +68,15 @@ where
     }
 }
 
+impl<K, V> IntoIterator for U64Map<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
 impl<K: Clone, V: Clone> Clone for U64Map<K, V> {
     fn clone(&self) -> Self {
         Self(self.0.clone())
@@ -139,7 +139,7 @@ impl<'a, E: Entity, T> DoubleEndedIterator for TreeIter<'a, E, T> {
 #########################################################
 */
 
-fn filter_map<'a, E, T>((i, slot): (usize, &'a Slot<T>)) -> Option<(E, Option<&'a T>)>
+fn filter_map<E, T>((i, slot): (usize, &Slot<T>)) -> Option<(E, Option<&T>)>
 where
     E: Entity
 {
@@ -170,11 +170,10 @@ where
     }
 }
 
+type FilterFn<'a, E, T> = fn((usize, &'a Slot<T>)) -> Option<(E, Option<&'a T>)>;
+
 pub struct IndexMapIter<'a, E: Entity, T> {
-    inner: FilterMap<
-        Enumerate<Iter<'a, Slot<T>>>,
-        fn((usize, &'a Slot<T>)) -> Option<(E, Option<&'a T>)>
-    >,
+    inner: FilterMap<Enumerate<Iter<'a, Slot<T>>>, FilterFn<'a, E, T>>,
 }
 
 impl<'a, E, T> Iterator for IndexMapIter<'a, E, T>
@@ -208,7 +207,7 @@ where
 #########################################################
 */
 
-fn filter_mut<'a, E, T>((i, slot): (usize, &'a mut Slot<T>)) -> Option<(E, Option<&'a mut T>)>
+fn filter_mut<E, T>((i, slot): (usize, &mut Slot<T>)) -> Option<(E, Option<&mut T>)>
 where
     E: Entity
 {
@@ -234,11 +233,10 @@ impl<'a, E: Entity, T> IntoIterator for &'a mut IndexMap<E, T> {
     }
 }
 
+type FilterMutFn<'a, E, T> = fn((usize, &'a mut Slot<T>)) -> Option<(E, Option<&'a mut T>)>;
+
 pub struct IndexMapIterMut<'a, E: Entity, T> {
-    inner: FilterMap<
-        Enumerate<IterMut<'a, Slot<T>>>,
-        fn((usize, &'a mut Slot<T>)) -> Option<(E, Option<&'a mut T>)>
-    >,
+    inner: FilterMap<Enumerate<IterMut<'a, Slot<T>>>, FilterMutFn<'a, E, T>>,
 }
 
 impl<'a, E: Entity, T> Iterator for IndexMapIterMut<'a, E, T> {
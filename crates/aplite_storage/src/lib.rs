@@ -4,11 +4,13 @@ mod entity;
 mod hash;
 mod index_map;
 mod slot;
+mod grid;
 
 pub use tree::*;
 pub use entity::Entity;
 pub use hash::U64Map;
 pub use index_map::IndexMap;
+pub use grid::SpatialGrid;
 
 #[derive(Debug)]
 pub enum Error {
@@ -45,9 +45,18 @@ impl<E: Entity, T> Tree<E, T> {
     /// You can later add children, next siblings, or set the parent to this entity
     pub fn insert(&mut self, data: T) -> E {
         let entity = self.data.insert(data);
-        self.first_child.push(None);
-        self.next_sibling.push(None);
-        self.parent.push(None);
+        let index = entity.index();
+
+        // the index of a removed entity may be reused
+        if index < self.parent.len() {
+            self.first_child[index] = None;
+            self.next_sibling[index] = None;
+            self.parent[index] = None;
+        } else {
+            self.first_child.push(None);
+            self.next_sibling.push(None);
+            self.parent.push(None);
+        }
         entity
     }
 
@@ -63,31 +72,42 @@ impl<E: Entity, T> Tree<E, T> {
         self.data.get_mut(entity)
     }
 
+    /// Remove the entity and all of its descendants. Returns the removed entities, starting with `entity`
     pub fn remove(&mut self, entity: E) -> Vec<E> {
-        let mut to_remove = vec![entity];
-        let mut current = entity;
+        // a stale entity may share its index with a live one
+        if !self.contains(&entity) { return Vec::new() }
 
-        while let Some(children) = self.get_all_children(&current) {
-            children.iter().for_each(|child| current = *child);
-            to_remove.extend_from_slice(&children);
-        }
+        self.detach(&entity);
+
+        let mut to_remove = vec![entity];
+        to_remove.extend(self.get_all_members_of(&entity));
 
-        // shifting
-        if let Some(prev) = self.get_prev_sibling(&entity).copied() {
-            self.next_sibling[prev.index()] = self.get_next_sibling(&entity).copied();
-        } else if let Some(parent) = self.get_parent(&entity).copied() {
-            self.first_child[parent.index()] = self.get_next_sibling(&entity).copied();
-        }
-        
         to_remove
             .iter()
             .for_each(|entity| {
                 self.data.remove(entity);
                 self.parent[entity.index()] = None;
+                self.first_child[entity.index()] = None;
+                self.next_sibling[entity.index()] = None;
             });
         to_remove
     }
 
+    /// Unlink the entity from its parent & siblings, while keeping its own descendants.
+    /// The entity can be attached again with [`Tree::add_child`] or [`Tree::add_sibling`]
+    pub fn detach(&mut self, entity: &E) {
+        let prev = self.get_prev_sibling(entity).copied();
+        let next = self.next_sibling[entity.index()].take();
+
+        match (prev, self.get_parent(entity).copied()) {
+            (Some(prev), _) => self.next_sibling[prev.index()] = next,
+            (None, Some(parent)) => self.first_child[parent.index()] = next,
+            (None, None) => {},
+        }
+
+        self.parent[entity.index()] = None;
+    }
+
     /// Adding an entity to be the child of a parent.
     /// This will calculate if it's the first child of the parent,
     /// or the next sibling of parent's last child.
@@ -397,7 +417,7 @@ mod tree_test {
                 tree.add_child(parent, id);
             }
             if i > 0 && i % 3 == 0 {
-                parent = tree.get_first_child(&TestId::new(1, 0)).map(|e| *e);
+                parent = tree.get_first_child(&TestId::new(1, 0)).copied();
             } else {
                 parent = Some(id);
             }
@@ -468,4 +488,39 @@ mod tree_test {
 
         eprintln!("{tree:?}");
     }
+
+    #[test]
+    fn remove_whole_subtree() {
+        let mut tree = setup_tree();
+        let before = tree.len();
+
+        // 2 has two children, and the subtree of 3 has to be removed too
+        let removed = tree.remove(TestId::new(2, 0));
+        assert_eq!(removed.len(), before - tree.len());
+        assert!(removed.iter().all(|id| !tree.contains(id)));
+        assert!(tree.get_first_child(&TestId::new(1, 0)).is_none());
+
+        // the reused index starts without any relation
+        let reuse = tree.insert(());
+        assert!(tree.get_first_child(&reuse).is_none());
+        assert!(tree.get_next_sibling(&reuse).is_none());
+        assert!(tree.get_parent(&reuse).is_none());
+    }
+
+    #[test]
+    fn detach_and_reattach() {
+        let mut tree: Tree<TestId, ()> = Tree::new();
+        let parent = tree.insert(());
+        let children = [(); 3].map(|_| tree.insert(()));
+        children.iter().for_each(|child| tree.add_child(&parent, *child));
+
+        tree.detach(&children[0]);
+        tree.add_child(&parent, children[0]);
+        assert_eq!(tree.get_all_children(&parent), Some(vec![children[1], children[2], children[0]]));
+
+        tree.detach(&children[2]);
+        assert_eq!(tree.get_all_children(&parent), Some(vec![children[1], children[0]]));
+        assert!(tree.get_parent(&children[2]).is_none());
+        assert!(tree.get_next_sibling(&children[2]).is_none());
+    }
 }
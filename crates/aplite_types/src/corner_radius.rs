@@ -1,5 +1,5 @@
 #[repr(C, align(16))]
-#[derive(Debug, Default, Clone, Copy)]
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct CornerRadius {
     pub tl: f32,
     pub bl: f32,
@@ -0,0 +1,45 @@
+/// A size along one axis, which may depend on the content box of the parent
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum Length {
+    /// In logical pixels
+    Px(f32),
+    /// Of the content box of the parent, where 100 is the whole box
+    Percent(f32),
+    /// Takes all the space it can inside the parent
+    Fill,
+    /// Fits the content
+    #[default]
+    Auto,
+}
+
+impl Length {
+    /// The length in pixels, given the length of the parent's content box if known.
+    /// Returns [`None`] if it depends on the content, or on an unknown parent
+    #[inline(always)]
+    pub fn resolve(self, parent: Option<f32>) -> Option<f32> {
+        match self {
+            Self::Px(value) => Some(value),
+            Self::Percent(percent) => parent.map(|parent| parent * percent / 100.0),
+            Self::Fill => parent,
+            Self::Auto => None,
+        }
+    }
+
+    /// Whether the length depends on the parent
+    #[inline(always)]
+    pub const fn is_relative(&self) -> bool {
+        matches!(self, Self::Percent(_) | Self::Fill)
+    }
+}
+
+impl From<f32> for Length {
+    fn from(value: f32) -> Self {
+        Self::Px(value)
+    }
+}
+
+impl From<u32> for Length {
+    fn from(value: u32) -> Self {
+        Self::Px(value as f32)
+    }
+}
@@ -1,4 +1,5 @@
 mod fraction;
+mod length;
 mod matrix;
 mod size;
 mod vector;
@@ -11,6 +12,7 @@ pub use corner_radius::CornerRadius;
 pub use size::{gcd, Size};
 pub use matrix::Matrix3x2;
 pub use fraction::Fraction;
+pub use length::Length;
 
 pub use vector::{Vec2f, Vec2u};
 pub use vector::{vec2f, vec2u};
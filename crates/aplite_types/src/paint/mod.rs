@@ -13,6 +13,8 @@ pub enum Paint {
 pub enum PaintRef<'a> {
     Color(&'a Rgba<u8>),
     Image(ImageRef),
+    /// An alpha mask, tinted with the color. Used to render glyphs
+    Glyph(ImageRef, &'a Rgba<u8>),
 }
 
 impl Paint {
@@ -90,6 +90,16 @@ impl Rect {
         && (self.y..self.max_y()).contains(&p.y)
     }
 
+    /// The overlapping area of the two rects, with zero size if they don't overlap
+    pub fn intersect(&self, other: &Self) -> Self {
+        let x = self.x.max(other.x);
+        let y = self.y.max(other.y);
+        let max_x = self.max_x().min(other.max_x());
+        let max_y = self.max_y().min(other.max_y());
+
+        Self::new(x, y, (max_x - x).max(0.0), (max_y - y).max(0.0))
+    }
+
     #[inline(always)]
     pub fn adjust_width(&mut self, aspect_ratio: Fraction) {
         self.width = self.height * aspect_ratio
@@ -136,7 +146,7 @@ impl Eq for Rect {}
 
 impl PartialOrd for Rect {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.size().partial_cmp(&other.size())
+        Some(self.cmp(other))
     }
 }
 
@@ -114,7 +114,7 @@ impl Eq for Size {}
 
 impl PartialOrd for Size {
     fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        self.area().partial_cmp(&other.area())
+        Some(self.cmp(other))
     }
 }
 
@@ -146,7 +146,7 @@ pub fn gcd(a: f32, b: f32) -> f32 {
         if rem == 0.0 { break }
         let temp = ret;
         ret = rem;
-        rem = temp / rem;
+        rem = temp % rem;
     }
     ret
 }
@@ -161,6 +161,14 @@ mod gcd_test {
         let height = 1600.;
         let gcd = gcd(width, height);
         let fraction = [width/gcd, height/gcd];
-        assert_eq!(fraction, [5., 3.]);
+        assert_eq!(fraction, [8., 5.]);
+    }
+
+    #[test]
+    fn coprime_and_multiple() {
+        assert_eq!(gcd(1920., 1080.), 120.);
+        assert_eq!(gcd(9., 4.), 1.);
+        assert_eq!(gcd(30., 10.), 10.);
+        assert_eq!(gcd(10., 30.), 10.);
     }
 }
@@ -67,7 +67,7 @@ fn button_stack(
         .set_state(|s| {
             s.set_min_width(400.);
             s.set_align_h(AlignH::Center);
-            s.set_align_v(AlignV::Middle);
+            s.set_justify(Justify::Center);
             s.set_padding(Padding::splat(10.));
             s.set_spacing(5.);
         })
@@ -1,9 +1,9 @@
 use aplite::prelude::*;
 
 fn get_color(val: u32) -> Rgba<u8> {
-    if val % 3 == 0 {
+    if val.is_multiple_of(3) {
         Rgba::RED
-    } else if val % 2 == 0 {
+    } else if val.is_multiple_of(2) {
         Rgba::GREEN
     } else {
         Rgba::BLUE
@@ -11,7 +11,7 @@ fn get_color(val: u32) -> Rgba<u8> {
 }
 
 fn get_shape(val: u32) -> Shape {
-    if val % 2 == 0 {
+    if val.is_multiple_of(2) {
         Shape::Circle
     } else {
         Shape::RoundedRect
@@ -1,34 +1,44 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::time::Instant;
 
 use winit::dpi::{PhysicalPosition, PhysicalSize, LogicalSize};
-use winit::event_loop::{ActiveEventLoop, EventLoop};
+use winit::event_loop::{ActiveEventLoop, ControlFlow, EventLoop};
 use winit::window::{Window, WindowAttributes, WindowId};
-use winit::event::{ElementState, MouseButton, WindowEvent};
+use winit::event::{ElementState, Ime, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent};
 use winit::application::ApplicationHandler;
 
 use aplite_reactive::*;
-use aplite_types::Size;
-use aplite_renderer::Renderer;
+use aplite_types::{Size, Vec2f};
+use aplite_renderer::{Font, Renderer};
 use aplite_future::{block_on, Executor};
 
 use crate::prelude::ApliteResult;
 use crate::context::Context;
+use crate::context::cursor::{ClickSettings, CursorIcon};
 use crate::error::ApliteError;
 use crate::view::{IntoView, View, ViewId, VIEW_STORAGE};
+use crate::widget::set_default_font;
 
 pub(crate) const DEFAULT_SCREEN_SIZE: LogicalSize<u32> = LogicalSize::new(800, 600);
 
+/// How many logical pixels one line of mouse wheel scrolls
+const SCROLL_LINE_HEIGHT: f32 = 20.0;
+
 pub(crate) struct WindowHandle {
     pub(crate) window: Arc<Window>,
     pub(crate) root_id: ViewId,
+    /// The last icon set on the window
+    pub(crate) cursor_icon: CursorIcon,
 }
 
+type PendingViews = Box<dyn FnOnce(WindowId) -> Box<dyn IntoView>>;
+
 pub struct Aplite {
     cx: Context,
     renderer: Option<Renderer>,
     window: HashMap<WindowId, WindowHandle>,
-    pending_views: Option<Box<dyn FnOnce(WindowId) -> Box<dyn IntoView>>>,
+    pending_views: Option<PendingViews>,
     window_attributes_fn: Option<fn(&mut WindowAttributes)>,
 
     #[cfg(feature = "render_stats")]
@@ -69,6 +79,18 @@ impl Aplite {
         self
     }
 
+    /// Configure the double click & long press detection
+    pub fn set_click_settings(mut self, settings: ClickSettings) -> Self {
+        self.cx.set_click_settings(settings);
+        self
+    }
+
+    /// The [`Font`] used by every [`Text`](crate::widget::Text) which doesn't specify its own
+    pub fn set_default_font(self, font: Font) -> Self {
+        set_default_font(font);
+        self
+    }
+
     // pub fn with_background_color(self, color: Rgba<u8>) -> Self {
     //     let _ = color;
     //     self
@@ -116,6 +138,7 @@ impl Aplite {
         let window_handle = WindowHandle {
             window: Arc::clone(&window),
             root_id,
+            cursor_icon: CursorIcon::Default,
         };
 
         let renderer = block_on(async { Renderer::new(Arc::clone(&window)).await })?;
@@ -137,30 +160,116 @@ impl Aplite {
 
 // window event
 impl Aplite {
-    fn handle_resize(&mut self, size: PhysicalSize<u32>) {
+    fn handle_resize(&mut self, window_id: &WindowId, size: PhysicalSize<u32>) {
         if let Some(renderer) = self.renderer.as_mut()
         && size.width > 0 && size.height > 0
         {
             renderer.resize(size);
+            self.reflow(window_id);
         }
     }
 
-    fn set_scale_factor(&mut self, scale_factor: f64) {
-        if let Some(renderer) = self.renderer.as_mut() {
+    fn set_scale_factor(&mut self, window_id: &WindowId, scale_factor: f64) {
+        if let Some(renderer) = self.renderer.as_mut()
+        && let Some(WindowHandle { window, .. }) = self.window.get(window_id)
+        {
             renderer.set_scale_factor(scale_factor);
+            // the logical size changes, even if the physical one is kept
+            let size = window.inner_size();
+            if size.width > 0 && size.height > 0 {
+                renderer.resize(size);
+                self.reflow(window_id);
+            }
         }
     }
 
+    /// Lay out the content again with the logical size of the window
+    fn reflow(&mut self, window_id: &WindowId) {
+        if let Some(renderer) = self.renderer.as_ref()
+        && let Some(WindowHandle { window, root_id, .. }) = self.window.get(window_id)
+        {
+            let size = window.inner_size().to_logical::<f32>(renderer.scale_factor());
+            self.cx.resize_window(root_id, Size::new(size.width, size.height));
+        }
+        self.update_cursor_icon(window_id);
+    }
+
     fn handle_mouse_move(&mut self, window_id: &WindowId, pos: PhysicalPosition<f64>) {
         if let Some(renderer) = self.renderer.as_mut()
         && let Some(WindowHandle { root_id, .. }) = self.window.get(window_id) {
             let logical_pos = pos.to_logical::<f32>(renderer.scale_factor());
             self.cx.handle_mouse_move(root_id, (logical_pos.x, logical_pos.y));
         }
+        self.update_cursor_icon(window_id);
+    }
+
+    fn handle_click(&mut self, window_id: &WindowId, state: ElementState, button: MouseButton) {
+        if let Some(WindowHandle { root_id, .. }) = self.window.get(window_id) {
+            let focus = self.cx.focused();
+            self.cx.handle_click(root_id, state, button, Instant::now());
+            self.update_ime(window_id, focus);
+        }
+        self.update_cursor_icon(window_id);
+    }
+
+    /// Line deltas are converted into pixels, and Shift turns vertical scrolling into horizontal
+    fn handle_mouse_wheel(&mut self, window_id: &WindowId, delta: MouseScrollDelta) {
+        if let Some(renderer) = self.renderer.as_ref()
+        && let Some(WindowHandle { root_id, .. }) = self.window.get(window_id)
+        {
+            let (x, y) = match delta {
+                MouseScrollDelta::LineDelta(x, y) => (x * SCROLL_LINE_HEIGHT, y * SCROLL_LINE_HEIGHT),
+                MouseScrollDelta::PixelDelta(pos) => {
+                    let logical = pos.to_logical::<f32>(renderer.scale_factor());
+                    (logical.x, logical.y)
+                },
+            };
+
+            // the wheel reports how far the content moves, while the offset moves the other way
+            let delta = if self.cx.modifiers().shift_key() && x == 0.0 {
+                Vec2f::new(-y, 0.0)
+            } else {
+                Vec2f::new(-x, -y)
+            };
+
+            self.cx.handle_scroll(root_id, delta);
+        }
+        self.update_cursor_icon(window_id);
     }
 
-    fn handle_click(&mut self, state: ElementState, button: MouseButton) {
-        self.cx.handle_click(state, button);
+    fn handle_keyboard_input(&mut self, window_id: &WindowId, event: KeyEvent) {
+        if let Some(WindowHandle { root_id, .. }) = self.window.get(window_id) {
+            let focus = self.cx.focused();
+            self.cx.handle_keyboard_input(root_id, &event);
+            self.update_ime(window_id, focus);
+        }
+    }
+
+    fn handle_ime(&mut self, window_id: &WindowId, ime: Ime) {
+        if let Some(WindowHandle { root_id, .. }) = self.window.get(window_id) {
+            self.cx.handle_ime(root_id, ime);
+        }
+    }
+
+    /// Only accept IME input while a widget is focused
+    fn update_ime(&self, window_id: &WindowId, prev_focus: Option<ViewId>) {
+        let focus = self.cx.focused();
+        if focus != prev_focus
+        && let Some(WindowHandle { window, .. }) = self.window.get(window_id)
+        {
+            window.set_ime_allowed(focus.is_some());
+        }
+    }
+
+    /// Follow the hovered widget & the drag state
+    fn update_cursor_icon(&mut self, window_id: &WindowId) {
+        let icon = self.cx.cursor_icon();
+        if let Some(handle) = self.window.get_mut(window_id)
+        && handle.cursor_icon != icon
+        {
+            handle.cursor_icon = icon;
+            handle.window.set_cursor(winit::window::CursorIcon::from(icon));
+        }
     }
 
     fn handle_close_request(&mut self, window_id: &WindowId, event_loop: &ActiveEventLoop) {
@@ -212,13 +321,32 @@ impl ApplicationHandler for Aplite {
         match event {
             WindowEvent::CloseRequested => self.handle_close_request(&window_id, event_loop),
             WindowEvent::RedrawRequested => self.handle_redraw_request(&window_id, event_loop),
-            WindowEvent::Resized(size) => self.handle_resize(size),
-            WindowEvent::MouseInput { state, button, .. } => self.handle_click(state, button),
+            WindowEvent::Resized(size) => self.handle_resize(&window_id, size),
+            WindowEvent::MouseInput { state, button, .. } => self.handle_click(&window_id, state, button),
+            WindowEvent::KeyboardInput { event, .. } => self.handle_keyboard_input(&window_id, event),
+            WindowEvent::ModifiersChanged(modifiers) => self.cx.set_modifiers(modifiers.state()),
+            WindowEvent::Ime(ime) => self.handle_ime(&window_id, ime),
             WindowEvent::CursorMoved { position, .. } => self.handle_mouse_move(&window_id, position),
-            WindowEvent::ScaleFactorChanged { scale_factor, .. } => self.set_scale_factor(scale_factor),
+            WindowEvent::MouseWheel { delta, .. } => self.handle_mouse_wheel(&window_id, delta),
+            WindowEvent::ScaleFactorChanged { scale_factor, .. } => self.set_scale_factor(&window_id, scale_factor),
             _ => {}
         }
     }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        self.cx.handle_long_press(Instant::now());
+        self.cx.handle_tooltip();
+
+        // wake up when the held button becomes a long press, or when a tooltip is due
+        let deadline = match (self.cx.long_press_deadline(), self.cx.tooltip_deadline()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        match deadline {
+            Some(deadline) => event_loop.set_control_flow(ControlFlow::WaitUntil(deadline)),
+            None => event_loop.set_control_flow(ControlFlow::Wait),
+        }
+    }
 }
 
 #[allow(unused)]
@@ -1,27 +1,83 @@
 pub(crate) mod cursor;
+pub(crate) mod keyboard;
 pub mod layout;
 
+use std::time::Instant;
+
 use aplite_reactive::*;
-use aplite_renderer::Scene;
-use aplite_types::Vec2f;
+use aplite_renderer::{Scene, Shape};
+use aplite_types::{CornerRadius, PaintRef, Rect, Rgba, Size, Vec2f};
 
-use crate::view::{VIEW_STORAGE, ViewId};
-use crate::widget::{CALLBACKS, WidgetEvent};
+use crate::state::{WidgetState, get_transform, sd_rounded_rect};
+use crate::view::{VIEW_STORAGE, ViewId, update_views};
+use crate::widget::{
+    CALLBACKS,
+    SCROLLBAR_COLOR,
+    SELECTION_COLOR,
+    Placement,
+    PopupOptions,
+    WidgetEvent,
+    call_on_dismiss,
+    create_popup,
+    create_tooltip,
+    has_tooltip,
+    take_popup_requests,
+};
 
-use cursor::{Cursor, MouseAction, MouseButton};
+use cursor::{ClickSettings, Cursor, CursorIcon, MouseAction, MouseButton, MouseEvent, MouseHover};
+use keyboard::{Keyboard, KeyEvent};
 use layout::{LayoutContext, calculate_size_recursive};
 
 // FIXME: use this as the main building block to build the widget
 pub struct Context {
     cursor: Cursor,
+    keyboard: Keyboard,
     pending_event: Vec<ViewId>,
+    scroll_drag: Option<ScrollDrag>,
+    /// The popups of every window, from the bottom to the top
+    overlays: Vec<Overlay>,
+    tooltip: Option<Tooltip>,
+    /// A click hides the tooltip until the cursor leaves the widget
+    tooltip_dismissed: bool,
+}
+
+/// A scrollbar thumb being dragged
+#[derive(Debug, Clone, Copy)]
+struct ScrollDrag {
+    id: ViewId,
+    vertical: bool,
+    /// the cursor position & the scroll offset when the thumb was grabbed
+    grab: Vec2f,
+    start: Vec2f,
+}
+
+/// A popup mounted on the overlay layer of a window, with its own root
+#[derive(Debug, Clone, Copy)]
+struct Overlay {
+    window: ViewId,
+    anchor: ViewId,
+    id: ViewId,
+    options: PopupOptions,
+}
+
+/// The tooltip being shown, drawn over the window of its owner
+#[derive(Debug, Clone, Copy)]
+struct Tooltip {
+    owner: ViewId,
+    window: ViewId,
+    id: ViewId,
 }
 
 impl Default for Context {
     fn default() -> Self {
         Self {
             cursor: Cursor::new(),
+            keyboard: Keyboard::new(),
             pending_event: Vec::with_capacity(16),
+            scroll_drag: None,
+            overlays: Vec::new(),
+            tooltip: None,
+            tooltip_dismissed: false,
         }
     }
 }
@@ -47,6 +103,30 @@ impl Context {
         VIEW_STORAGE.with(|s| s.dirty.set(true))
     }
 
+    /// Forget the ids of the views which have been removed from the tree
+    pub(crate) fn forget_removed_views(&mut self) {
+        let removed = |id: &ViewId| VIEW_STORAGE.with(|s| !s.tree.borrow().contains(id));
+
+        if self.cursor.hover.curr.is_some_and(|id| removed(&id)) {
+            self.cursor.hover.curr = None;
+            self.cursor.is_dragging = false;
+        }
+        if self.cursor.hover.prev.is_some_and(|id| removed(&id)) {
+            self.cursor.hover.prev = None;
+        }
+        if self.scroll_drag.is_some_and(|drag| removed(&drag.id)) {
+            self.scroll_drag = None;
+        }
+        if self.keyboard.focus.is_some_and(|id| removed(&id)) {
+            self.keyboard.focus = None;
+        }
+        if self.tooltip.is_some_and(|tooltip| removed(&tooltip.owner) || removed(&tooltip.id)) {
+            self.hide_tooltip();
+        }
+        self.close_orphan_overlays();
+        self.pending_event.retain(|id| !removed(id));
+    }
+
     // pub(crate) fn toggle_clean(&self) {
     //     VIEW_STORAGE.with(|s| s.dirty.set(None))
     // }
@@ -64,6 +144,22 @@ impl Context {
         LayoutContext::new(*root_id).calculate();
         Self::toggle_dirty();
     }
+
+    /// Resize the root to the logical size of the window, then reflow the content & the popups
+    pub(crate) fn resize_window(&mut self, root_id: &ViewId, size: Size) {
+        VIEW_STORAGE.with(|s| {
+            if let Some(state) = s.tree.borrow_mut().get_mut(root_id) {
+                state.set_size(size);
+            }
+        });
+        self.layout_the_whole_window(root_id);
+
+        self.overlays
+            .iter()
+            .filter(|overlay| &overlay.window == root_id)
+            .for_each(place_popup);
+        self.detect_hover(root_id);
+    }
 }
 
 // #########################################################
@@ -77,25 +173,57 @@ impl Context {
         if VIEW_STORAGE.with(|s| s.get_all_members_of(root_id).is_empty()) { return }
         self.cursor.hover.pos = pos.into();
 
+        if let Some(drag) = self.scroll_drag {
+            self.drag_scrollbar(drag);
+            return;
+        }
+
         #[cfg(feature = "cursor_stats")] let start = std::time::Instant::now();
-        self.detect_hover();
+        self.detect_hover(root_id);
         #[cfg(feature = "cursor_stats")] eprint!("{:?}     \r", start.elapsed());
 
+        // repaint to apply or revert the hover color
+        if self.cursor.hover.curr != self.cursor.hover.prev {
+            Self::toggle_dirty();
+        }
+
         self.handle_hover();
     }
 
-    fn detect_hover(&mut self) {
+    /// Finds the topmost hoverable widget under the cursor.
+    /// Widgets are tested in reverse paint order, so the one drawn last wins
+    fn detect_hover(&mut self, root_id: &ViewId) {
         if !self.cursor.is_clicking() {
+            let pos = self.cursor.hover.pos;
+            let layers = self.input_layers(root_id);
+            let raised = self.raised();
             let hovered = VIEW_STORAGE.with(|s| {
-                s.hoverable
+                let tree = s.tree.borrow();
+                let candidates = s.hoverable
                     .borrow()
+                    .query(pos)
                     .iter()
-                    .find(|&id| {
-                        let tree = s.tree.borrow();
-                        let state = tree.get(&id).unwrap();
-                        state.detect_hover(&self.cursor)
+                    .filter(|&id| {
+                        tree.get(id).is_some_and(|state| state.detect_hover(&self.cursor))
+                            && !is_clipped(&tree, id, pos)
                     })
                     .copied()
+                    .collect::<Vec<_>>();
+
+                // the topmost layer containing a candidate, unless an overlay above covers the cursor
+                layers.iter().find_map(|layer| {
+                    let hovered = candidates
+                        .iter()
+                        .filter(|id| layer_of(&tree, id) == *layer)
+                        .copied()
+                        .reduce(|top, id| if paints_after(&tree, &id, &top, raised) { id } else { top });
+
+                    match hovered {
+                        Some(id) => Some(Some(id)),
+                        None => covers(&tree, layer, pos).then_some(None),
+                    }
+                })
+                .flatten()
             });
 
             match hovered {
@@ -106,106 +234,1693 @@ impl Context {
                     self.cursor.hover.prev = self.cursor.hover.curr.take();
                 },
             }
+
+            let MouseHover { curr, prev, .. } = self.cursor.hover;
+            if curr != prev {
+                self.cursor.hover.since = Instant::now();
+                self.tooltip_dismissed = false;
+                self.hide_tooltip();
+
+                if let Some(prev) = prev {
+                    self.dispatch(&prev, WidgetEvent::HoverLeave, Vec2f::default());
+                }
+                if let Some(curr) = curr {
+                    self.dispatch(&curr, WidgetEvent::HoverEnter, Vec2f::default());
+                }
+            }
         }
     }
 
-    pub(crate) fn handle_hover(&mut self) {
-        if !self.cursor.is_idling() {
-            if let Some(hover_id) = self.cursor.hover.curr {
-                let dragable = VIEW_STORAGE.with(|s| {
-                    s.tree
-                        .borrow()
-                        .get(&hover_id)
-                        .unwrap()
-                        .dragable
-                });
-                if self.cursor.is_dragging(&hover_id) && dragable {
-                    self.cursor.is_dragging = true;
-                    self.handle_drag(&hover_id);
+    /// The dragged widget, which is raised to the front along with its ancestors
+    fn raised(&self) -> Option<ViewId> {
+        let id = self.cursor.hover.curr.filter(|_| self.cursor.is_dragging)?;
+        VIEW_STORAGE
+            .with(|s| s.tree.borrow().get(&id).is_some_and(|state| state.dragable))
+            .then_some(id)
+    }
+
+    /// The icon of the hovered widget, or of its closest ancestor which has one
+    pub(crate) fn cursor_icon(&self) -> CursorIcon {
+        let Some(hover_id) = self.cursor.hover.curr else { return CursorIcon::Default };
+
+        let icon = VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            let mut current = Some(&hover_id);
+            while let Some(id) = current {
+                if let Some(icon) = tree.get(id).and_then(|state| state.cursor_icon) {
+                    return icon;
                 }
+                current = tree.get_parent(id);
             }
+            CursorIcon::Default
+        });
+
+        match icon {
+            CursorIcon::Grab if self.cursor.is_dragging => CursorIcon::Grabbing,
+            icon => icon,
+        }
+    }
+
+    pub(crate) fn handle_hover(&mut self) {
+        let Some(hover_id) = self.cursor.hover.curr else { return };
+        if !self.cursor.is_dragging && !self.cursor.is_dragging(&hover_id) { return }
+
+        if !self.cursor.is_dragging {
+            self.cursor.is_dragging = true;
+            self.dispatch(&hover_id, WidgetEvent::DragStart, Vec2f::default());
+        }
+
+        let dragable = VIEW_STORAGE.with(|s| {
+            s.tree
+                .borrow()
+                .get(&hover_id)
+                .is_some_and(|state| state.dragable)
+        });
+        if dragable {
+            self.handle_drag(&hover_id);
         }
+
+        let delta = self.cursor.take_drag_delta();
+        self.dispatch(&hover_id, WidgetEvent::DragMove, delta);
     }
 
     fn handle_drag(&mut self, hover_id: &ViewId) {
         let pos = self.cursor.hover.pos - self.cursor.click.offset;
-        VIEW_STORAGE.with(|s| {
-            let mut tree = s.tree.borrow_mut();
-            let state = tree.get_mut(hover_id).unwrap();
-            state.rect.set_pos(pos.into());
+        let moved = VIEW_STORAGE.with(|s| {
+            s.tree
+                .borrow_mut()
+                .get_mut(hover_id)
+                .map(|state| {
+                    state.rect.set_pos(pos);
+                    s.update_bounds(hover_id, state);
+                })
+                .is_some()
+        });
+        if moved {
             LayoutContext::new(*hover_id).calculate();
             Self::toggle_dirty();
-        });
+        }
     }
 
-    pub(crate) fn handle_click(&mut self, action: impl Into<MouseAction>, button: impl Into<MouseButton>) {
-        self.cursor.set_click_state(action.into(), button.into());
-        if let Some(hover_id) = self.cursor.hover.curr.as_ref() {
-            VIEW_STORAGE.with(|s| {
-                let mut tree = s.tree.borrow_mut();
-                let state = tree.get_mut(hover_id).unwrap();
-                let pos = state.rect.vec2f();
-                self.cursor.click.offset = self.cursor.click.pos - pos;
-                state.event = Some(WidgetEvent::LeftClick);
-            });
-            self.pending_event.push(*hover_id);
-        }
-        if self.cursor.state.action == MouseAction::Released {
-            self.pending_event
-                .drain(..)
-                .for_each(|id| {
-                    CALLBACKS.with(|cb| {
-                        if let Some(callbacks) = cb.borrow_mut().get_mut(&id)
-                        && let MouseButton::Left = self.cursor.state.button
-                        && let Some(callback) = callbacks.get_mut(&WidgetEvent::LeftClick)
-                        {
-                            eprintln!("{id:?} run callback");
-                            callback();
-                        }
-                    })
+    /// Handle a press or a release of a mouse button, which happened `at` the given time
+    pub(crate) fn handle_click(
+        &mut self,
+        root_id: &ViewId,
+        action: impl Into<MouseAction>,
+        button: impl Into<MouseButton>,
+        at: Instant,
+    ) {
+        self.cursor.set_click_state(action.into(), button.into(), at);
+        // the scrollbars take the click before the widgets
+        if self.cursor.state.action == MouseAction::Released
+        && self.scroll_drag.take().is_some()
+        {
+            return;
+        }
+        if self.cursor.is_clicking() {
+            self.dismiss_overlays(root_id);
+        }
+        if self.cursor.is_clicking()
+        && self.cursor.state.button == MouseButton::Left
+        && self.grab_scrollbar(root_id)
+        {
+            return;
+        }
+        if self.cursor.is_clicking() {
+            self.tooltip_dismissed = true;
+            self.hide_tooltip();
+
+            let focus = self.cursor.hover.curr.filter(is_focusable);
+            self.set_focus(focus);
+
+            if let Some(hover_id) = self.cursor.hover.curr {
+                let event = self.cursor.click_event();
+                VIEW_STORAGE.with(|s| {
+                    if let Some(state) = s.tree.borrow_mut().get_mut(&hover_id) {
+                        let pos = state.rect.vec2f();
+                        self.cursor.click.offset = self.cursor.click.pos - pos;
+                        state.event = event;
+                    }
                 });
+                self.pending_event.push(hover_id);
 
-            if let Some(hover_id) = self.cursor.hover.curr.as_ref() {
+                // repaint to apply the click color
+                Self::toggle_dirty();
+            }
+            return;
+        }
+
+        // a release which ends a drag or a long press isn't a click
+        let (events, delta) = if self.cursor.is_dragging {
+            (vec![WidgetEvent::DragEnd], self.cursor.take_drag_delta())
+        } else if self.cursor.is_long_pressed {
+            (vec![], Vec2f::default())
+        } else {
+            let mut events = Vec::from_iter(self.cursor.click_event());
+            if events == [WidgetEvent::LeftClick] && self.cursor.click_count == 2 {
+                events.push(WidgetEvent::DoubleClick);
+            }
+            (events, Vec2f::default())
+        };
+
+        std::mem::take(&mut self.pending_event)
+            .iter()
+            .for_each(|id| {
                 VIEW_STORAGE.with(|s| {
-                    let mut tree = s.tree.borrow_mut();
-                    let state = tree.get_mut(hover_id).unwrap();
-                    state.event = None;
+                    if let Some(state) = s.tree.borrow_mut().get_mut(id) {
+                        state.event = None;
+                    }
                 });
+                events
+                    .iter()
+                    .for_each(|event| self.dispatch(id, *event, delta));
+            });
+
+        self.cursor.is_dragging = false;
+
+        // repaint to revert the click color
+        Self::toggle_dirty();
+    }
+
+    /// Send [`WidgetEvent::LongPress`] if the pressed widget has been held long enough
+    pub(crate) fn handle_long_press(&mut self, now: Instant) {
+        if let Some(deadline) = self.cursor.long_press_deadline()
+        && now >= deadline
+        && let Some(target) = self.cursor.press.and_then(|press| press.target)
+        {
+            self.cursor.is_long_pressed = true;
+            self.dispatch(&target, WidgetEvent::LongPress, Vec2f::default());
+        }
+    }
+
+    #[inline(always)]
+    pub(crate) fn long_press_deadline(&self) -> Option<Instant> {
+        self.cursor.long_press_deadline()
+    }
+
+    pub(crate) fn set_click_settings(&mut self, settings: ClickSettings) {
+        self.cursor.settings = settings;
+    }
+
+    /// Send the mouse event to the target, and propagate it through the target's ancestors
+    fn dispatch(&self, target: &ViewId, kind: WidgetEvent, delta: Vec2f) {
+        // from the target up to the root
+        let path = VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            if !tree.contains(target) { return Vec::new() }
+
+            let mut path = vec![*target];
+            if kind.bubbles() {
+                let mut current = target;
+                while let Some(parent) = tree.get_parent(current) {
+                    path.push(*parent);
+                    current = parent;
+                }
             }
+            path
+        });
+        if path.is_empty() { return }
 
-            self.cursor.is_dragging = false;
+        let pos = self.cursor.hover.pos;
+        let total_delta = match kind {
+            WidgetEvent::HoverEnter | WidgetEvent::HoverLeave => Vec2f::default(),
+            _ => pos - self.cursor.click.pos,
+        };
+        let mut event = MouseEvent {
+            kind,
+            button: self.cursor.state.button,
+            target: *target,
+            current: *target,
+            pos,
+            local: Vec2f::default(),
+            delta,
+            total_delta,
+            click_count: self.cursor.click_count,
+            modifiers: self.keyboard.modifiers,
+            stopped: Default::default(),
+            handled: Default::default(),
+        };
+
+        let capture = path.iter().rev().map(|id| (id, true));
+        let bubble = path.iter().map(|id| (id, false));
+
+        for (id, is_capture) in capture.chain(bubble) {
+            if event.is_propagation_stopped() { break }
+
+            let origin = VIEW_STORAGE.with(|s| {
+                s.tree.borrow().get(id).map(|state| state.rect.vec2f()).unwrap_or_default()
+            });
+            event.current = *id;
+            event.local = pos - origin;
+
+            CALLBACKS.with(|cb| {
+                if let Some(callbacks) = cb.borrow_mut().get_mut(id) {
+                    let phase = if is_capture { &mut callbacks.capture } else { &mut **callbacks };
+                    if let Some(callback) = phase.get_mut(&kind) {
+                        callback.call_with_mouse(&event);
+                    }
+                }
+            });
         }
     }
 }
 
 // #########################################################
 // #                                                       #
-// #                         Render                        #
+// #                     Scroll Event                      #
 // #                                                       #
 // #########################################################
 
 impl Context {
-    pub(crate) fn prepare_data(&self, root_id: ViewId, mut scene: Scene<'_>) {
-        VIEW_STORAGE.with(|s| {
-            s.get_all_members_of(&root_id)
+    /// Scroll the innermost [`ScrollView`](crate::widget::ScrollView) under the cursor by `delta`, in logical pixels
+    pub(crate) fn handle_scroll(&mut self, root_id: &ViewId, delta: Vec2f) {
+        let pos = self.cursor.hover.pos;
+        let layers = self.input_layers(root_id);
+        let raised = self.raised();
+        let target = VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            layers.iter().find_map(|layer| {
+                // the innermost one comes last in paint order
+                let target = members_in_paint_order(&tree, layer, raised)
+                    .into_iter()
+                    .rev()
+                    .find(|id| {
+                        let state = tree.get(id).unwrap();
+                        state.scroll.is_some()
+                            && state.rect.contains(pos)
+                            && !is_clipped(&tree, id, pos)
+                    });
+
+                match target {
+                    Some(id) => Some(Some(id)),
+                    None => covers(&tree, layer, pos).then_some(None),
+                }
+            })
+            .flatten()
+        });
+
+        if let Some(id) = target {
+            let offset = get_scroll_offset(&id);
+            self.set_scroll_offset(&id, offset + delta);
+        }
+    }
+
+    /// Start dragging a scrollbar thumb under the cursor. Returns `true` if one was grabbed
+    fn grab_scrollbar(&mut self, root_id: &ViewId) -> bool {
+        let pos = self.cursor.hover.pos;
+        let layers = self.input_layers(root_id);
+        let raised = self.raised();
+        let drag = VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            layers
                 .iter()
-                .for_each(|view_id| {
-                    let size = scene.size();
-                    let tree = s.tree.borrow();
-                    let state = tree.get(view_id).unwrap();
-
-                    let background = state.background.as_paint_ref();
-                    let border = state.border_color.as_paint_ref();
-                    let shape = state.shape;
-                    let transform = state.get_transform(size);
-                    let border_width = if state.border_width == 0.0 {
-                        5.0 / size.width
+                .flat_map(|layer| members_in_paint_order(&tree, layer, raised).into_iter().rev())
+                .find_map(|id| {
+                    let state = tree.get(&id).unwrap();
+                    let scroll = state.scroll.as_ref().filter(|scroll| scroll.scrollbar)?;
+                    if is_clipped(&tree, &id, pos) { return None }
+
+                    let [vertical, horizontal] = scroll.thumbs(state.rect);
+                    let vertical = if vertical.is_some_and(|thumb| thumb.contains(pos)) {
+                        true
+                    } else if horizontal.is_some_and(|thumb| thumb.contains(pos)) {
+                        false
                     } else {
-                        state.border_width / size.width
+                        return None;
                     };
 
-                    scene.draw(transform, background, border, border_width, shape);
+                    Some(ScrollDrag { id, vertical, grab: pos, start: scroll.offset })
+                })
+        });
+
+        self.scroll_drag = drag;
+        drag.is_some()
+    }
+
+    fn drag_scrollbar(&mut self, drag: ScrollDrag) {
+        let ratio = VIEW_STORAGE.with(|s| {
+            s.tree
+                .borrow()
+                .get(&drag.id)
+                .and_then(|state| {
+                    state.scroll
+                        .as_ref()
+                        .map(|scroll| scroll.drag_ratio(state.rect, drag.vertical))
+                })
+                .unwrap_or_default()
+        });
+
+        let moved = self.cursor.hover.pos - drag.grab;
+        let offset = if drag.vertical {
+            Vec2f::new(drag.start.x, drag.start.y + moved.y * ratio)
+        } else {
+            Vec2f::new(drag.start.x + moved.x * ratio, drag.start.y)
+        };
+
+        self.set_scroll_offset(&drag.id, offset);
+    }
+
+    /// Clamp & apply the offset, then move the content and notify the bound [`Signal`]
+    fn set_scroll_offset(&mut self, id: &ViewId, offset: Vec2f) {
+        let prev = get_scroll_offset(id);
+
+        let applied = VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            let state = tree.get_mut(id)?;
+            state.set_scroll_offset(offset);
+            state.scroll.as_ref().map(|scroll| (scroll.offset, scroll.signal))
+        });
+        let Some((offset, signal)) = applied else { return };
+
+        if offset == prev { return }
+
+        LayoutContext::new(*id).calculate();
+        if let Some(signal) = signal
+        && signal.get_untracked() != offset
+        {
+            signal.set(offset);
+        }
+
+        // the content moved under the cursor
+        let root_id = self.window_of(id);
+        self.detect_hover(&root_id);
+        Self::toggle_dirty();
+    }
+}
+
+fn get_scroll_offset(id: &ViewId) -> Vec2f {
+    VIEW_STORAGE.with(|s| {
+        s.tree
+            .borrow()
+            .get(id)
+            .and_then(|state| state.scroll.as_ref())
+            .map(|scroll| scroll.offset)
+            .unwrap_or_default()
+    })
+}
+
+/// Whether `pos` is outside of any clipping ancestor of the widget
+fn is_clipped(tree: &aplite_storage::Tree<ViewId, WidgetState>, id: &ViewId, pos: Vec2f) -> bool {
+    let mut current = tree.get_parent(id);
+    while let Some(parent) = current {
+        let Some(state) = tree.get(parent) else { break };
+        if state.clips_children() && !is_inside_clip(state, pos) {
+            return true;
+        }
+        current = tree.get_parent(parent);
+    }
+    false
+}
+
+/// The stacking order of the view among its siblings.
+/// The raised widget & its ancestors are above everything else
+fn stacking(tree: &aplite_storage::Tree<ViewId, WidgetState>, id: &ViewId, raised: Option<ViewId>) -> i32 {
+    if raised.is_some_and(|raised| &raised == id || tree.is_member_of(&raised, id)) {
+        return i32::MAX;
+    }
+    tree.get(id).map_or(0, |state| state.z_index)
+}
+
+/// The children sorted by their stacking order, the ones with the same order are kept in tree order
+fn paint_order(tree: &aplite_storage::Tree<ViewId, WidgetState>, parent: &ViewId, raised: Option<ViewId>) -> Vec<ViewId> {
+    let mut children = tree.get_all_children(parent).unwrap_or_default();
+    children.sort_by_key(|id| stacking(tree, id, raised));
+    children
+}
+
+/// The view and all of its members, in the order they are drawn
+fn members_in_paint_order(
+    tree: &aplite_storage::Tree<ViewId, WidgetState>,
+    id: &ViewId,
+    raised: Option<ViewId>,
+) -> Vec<ViewId> {
+    let mut members = vec![*id];
+    paint_order(tree, id, raised)
+        .iter()
+        .for_each(|child| members.extend(members_in_paint_order(tree, child, raised)));
+    members
+}
+
+/// Whether `a` is drawn after `b`: a descendant is drawn after its ancestors,
+/// and a sibling above another one after it, along with their whole subtree
+fn paints_after(
+    tree: &aplite_storage::Tree<ViewId, WidgetState>,
+    a: &ViewId,
+    b: &ViewId,
+    raised: Option<ViewId>,
+) -> bool {
+    let path = |id: &ViewId| {
+        let mut path = vec![*id];
+        let mut current = id;
+        while let Some(parent) = tree.get_parent(current) {
+            path.push(*parent);
+            current = parent;
+        }
+        path.reverse();
+        path
+    };
+    let (path_a, path_b) = (path(a), path(b));
+
+    let common = path_a.iter().zip(&path_b).take_while(|(a, b)| a == b).count();
+    match (path_a.get(common), path_b.get(common)) {
+        (Some(branch_a), Some(branch_b)) => {
+            let (za, zb) = (stacking(tree, branch_a, raised), stacking(tree, branch_b, raised));
+            if za != zb { return za > zb }
+
+            let mut current = branch_b;
+            while let Some(next) = tree.get_next_sibling(current) {
+                if next == branch_a { return true }
+                current = next;
+            }
+            false
+        },
+        // one is the ancestor of the other
+        (a, _) => a.is_some(),
+    }
+}
+
+/// Lay out the popup, next to its anchor & inside its window
+fn place_popup(overlay: &Overlay) {
+    let Overlay { window, anchor, id, options } = overlay;
+    let size = calculate_size_recursive(id);
+
+    VIEW_STORAGE.with(|s| {
+        let mut tree = s.tree.borrow_mut();
+        let bounds = tree.get(window).map(|state| state.rect.size()).unwrap_or_default();
+        let anchor_rect = tree.get(anchor).map(|state| state.rect).unwrap_or_default();
+        if let Some(state) = tree.get_mut(id) {
+            state.rect.set_pos(popup_position(anchor_rect, size, bounds, *options));
+            s.update_bounds(id, state);
+        }
+    });
+    LayoutContext::new(*id).calculate();
+}
+
+/// Same as the clip mask in the shader: the unrotated rect, with the corners of the shape
+fn is_inside_clip(state: &WidgetState, pos: Vec2f) -> bool {
+    let rect = state.shape_rect();
+    let p = pos - Vec2f::new(rect.center_x(), rect.center_y());
+    let half = Vec2f::new(rect.width, rect.height) / 2.0;
+    sd_rounded_rect(p, half, state.clip_corners()) <= 0.0
+}
+
+// #########################################################
+// #                                                       #
+// #                        Overlay                        #
+// #                                                       #
+// #########################################################
+
+impl Context {
+    /// Open or close the popups whose condition has changed
+    pub(crate) fn update_overlays(&mut self) {
+        take_popup_requests()
+            .into_iter()
+            .for_each(|(anchor, open)| {
+                let is_open = self.overlays.iter().any(|overlay| overlay.anchor == anchor);
+                match (open, is_open) {
+                    (true, false) => self.open_popup(anchor),
+                    (false, true) => self.close_popup(&anchor),
+                    _ => {},
+                }
+            });
+    }
+
+    fn open_popup(&mut self, anchor: ViewId) {
+        let window = self.window_of(&anchor);
+        let Some((view, options)) = create_popup(&anchor) else { return };
+
+        let id = view.node.id();
+        VIEW_STORAGE.with(|s| s.storage.borrow_mut().insert(id, view));
+
+        let overlay = Overlay { window, anchor, id, options };
+        place_popup(&overlay);
+        self.overlays.push(overlay);
+        Self::toggle_dirty();
+
+        // the popup may have opened under the cursor
+        self.detect_hover(&window);
+    }
+
+    fn close_popup(&mut self, anchor: &ViewId) {
+        let Some(index) = self.overlays.iter().position(|overlay| &overlay.anchor == anchor) else { return };
+        let overlay = self.overlays.remove(index);
+        VIEW_STORAGE.with(|s| s.remove(&overlay.id));
+        Self::toggle_dirty();
+
+        // the popups opened from inside of this one
+        self.close_orphan_overlays();
+        self.forget_removed_views();
+    }
+
+    /// Close the popups whose anchor has been removed
+    fn close_orphan_overlays(&mut self) {
+        let removed = |id: &ViewId| VIEW_STORAGE.with(|s| !s.tree.borrow().contains(id));
+        while let Some(index) = self.overlays.iter().position(|overlay| removed(&overlay.anchor)) {
+            let overlay = self.overlays.remove(index);
+            VIEW_STORAGE.with(|s| s.remove(&overlay.id));
+            Self::toggle_dirty();
+        }
+    }
+
+    /// Close the popups of the window under the press, from the top, until one contains the cursor,
+    /// the cursor is on its anchor, or a modal one is reached
+    fn dismiss_overlays(&mut self, root_id: &ViewId) {
+        let pos = self.cursor.hover.pos;
+        let overlays = self.overlays
+            .iter()
+            .rev()
+            .filter(|overlay| &overlay.window == root_id)
+            .copied()
+            .collect::<Vec<_>>();
+
+        for overlay in overlays {
+            let (inside, on_anchor) = VIEW_STORAGE.with(|s| {
+                let tree = s.tree.borrow();
+                (
+                    covers(&tree, &overlay.id, pos),
+                    tree.get(&overlay.anchor).is_some_and(|state| state.hit_test(pos)),
+                )
+            });
+            if inside || on_anchor { break }
+
+            if overlay.options.dismiss_on_outside_click {
+                self.close_popup(&overlay.anchor);
+                call_on_dismiss(&overlay.anchor);
+            }
+            if overlay.options.modal { break }
+        }
+    }
+
+    /// The root of the window the view is in, which may be inside of a popup
+    fn window_of(&self, id: &ViewId) -> ViewId {
+        let root = VIEW_STORAGE.with(|s| layer_of(&s.tree.borrow(), id));
+        self.overlays
+            .iter()
+            .find(|overlay| overlay.id == root)
+            .map_or(root, |overlay| overlay.window)
+    }
+
+    /// The roots receiving the input, from the top: the popups of the window down to the first modal one,
+    /// then the main tree if none of them is modal
+    fn input_layers(&self, root_id: &ViewId) -> Vec<ViewId> {
+        let mut layers = Vec::new();
+        for overlay in self.overlays.iter().rev().filter(|overlay| &overlay.window == root_id) {
+            layers.push(overlay.id);
+            if overlay.options.modal { return layers }
+        }
+        layers.push(*root_id);
+        layers
+    }
+}
+
+/// The root of the layer the view belongs to
+fn layer_of(tree: &aplite_storage::Tree<ViewId, WidgetState>, id: &ViewId) -> ViewId {
+    tree.get_root(id).copied().unwrap_or(*id)
+}
+
+/// The root of the layer and all of its members, in paint order
+fn layer_members(tree: &aplite_storage::Tree<ViewId, WidgetState>, layer: &ViewId) -> Vec<ViewId> {
+    std::iter::once(*layer)
+        .chain(tree.get_all_members_of(layer))
+        .collect()
+}
+
+/// Whether the root of the layer is under the cursor, which hides everything underneath
+fn covers(tree: &aplite_storage::Tree<ViewId, WidgetState>, layer: &ViewId, pos: Vec2f) -> bool {
+    tree.get(layer).is_some_and(|state| state.hit_test(pos))
+}
+
+/// Next to the anchor on the side of the placement, flipped to the opposite side when it doesn't fit
+/// but would fit there, then shifted to stay inside the window
+fn popup_position(anchor: Rect, size: Size, window: Size, options: PopupOptions) -> Vec2f {
+    let gap = options.gap;
+    let flip = |preferred: f32, opposite: f32, max: f32, len: f32| {
+        let fits = |start: f32| start >= 0.0 && start + len <= max;
+        if !fits(preferred) && fits(opposite) { opposite } else { preferred }
+    };
+
+    let pos = match options.placement {
+        Placement::Center => Vec2f::new(
+            (window.width - size.width) / 2.0,
+            (window.height - size.height) / 2.0,
+        ),
+        Placement::Bottom | Placement::Top => {
+            let below = anchor.max_y() + gap;
+            let above = anchor.y - gap - size.height;
+            let y = match options.placement {
+                Placement::Bottom => flip(below, above, window.height, size.height),
+                _ => flip(above, below, window.height, size.height),
+            };
+            Vec2f::new(anchor.x, y)
+        },
+        Placement::Left | Placement::Right => {
+            let right = anchor.max_x() + gap;
+            let left = anchor.x - gap - size.width;
+            let x = match options.placement {
+                Placement::Right => flip(right, left, window.width, size.width),
+                _ => flip(left, right, window.width, size.width),
+            };
+            Vec2f::new(x, anchor.y)
+        },
+    };
+
+    pos.min(Vec2f::new(window.width - size.width, window.height - size.height))
+        .max(Vec2f::default())
+}
+
+// #########################################################
+// #                                                       #
+// #                        Tooltip                        #
+// #                                                       #
+// #########################################################
+
+/// The distance between the cursor and the top left corner of the tooltip
+const TOOLTIP_OFFSET: Vec2f = Vec2f::new(12.0, 20.0);
+
+impl Context {
+    /// When the tooltip of the hovered widget is due, if it has one which isn't shown yet
+    pub(crate) fn tooltip_deadline(&self) -> Option<Instant> {
+        let hover_id = self.cursor.hover.curr?;
+        let pending = self.tooltip.is_none()
+            && !self.tooltip_dismissed
+            && !self.cursor.is_clicking()
+            && has_tooltip(&hover_id);
+
+        pending.then(|| self.cursor.hover.since + self.cursor.settings.tooltip_delay)
+    }
+
+    /// Show the tooltip once the hovered widget has been hovered long enough
+    pub(crate) fn handle_tooltip(&mut self) {
+        if self.tooltip_deadline().is_none_or(|deadline| Instant::now() < deadline) { return }
+        let Some(owner) = self.cursor.hover.curr else { return };
+        let window = self.window_of(&owner);
+        let Some(view) = create_tooltip(&owner) else { return };
+
+        let id = view.node.id();
+        VIEW_STORAGE.with(|s| s.storage.borrow_mut().insert(id, view));
+        let size = calculate_size_recursive(&id);
+
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            let bounds = tree.get(&window).map(|state| state.rect.size()).unwrap_or_default();
+            if let Some(state) = tree.get_mut(&id) {
+                state.rect.set_pos(tooltip_position(self.cursor.hover.pos, size, bounds));
+                s.update_bounds(&id, state);
+            }
+        });
+        LayoutContext::new(id).calculate();
+
+        self.tooltip = Some(Tooltip { owner, window, id });
+        Self::toggle_dirty();
+    }
+
+    fn hide_tooltip(&mut self) {
+        if let Some(tooltip) = self.tooltip.take() {
+            VIEW_STORAGE.with(|s| s.remove(&tooltip.id));
+            Self::toggle_dirty();
+        }
+    }
+}
+
+/// Below & to the right of the cursor, moved above it or to the left when it would leave the window
+fn tooltip_position(cursor: Vec2f, size: Size, window: Size) -> Vec2f {
+    let mut pos = cursor + TOOLTIP_OFFSET;
+    if pos.x + size.width > window.width {
+        pos.x = window.width - size.width;
+    }
+    if pos.y + size.height > window.height {
+        pos.y = cursor.y - size.height - TOOLTIP_OFFSET.y / 4.0;
+    }
+    pos.max(Vec2f::default())
+}
+
+// #########################################################
+// #                                                       #
+// #                    Keyboard Event                     #
+// #                                                       #
+// #########################################################
+
+impl Context {
+    #[inline(always)]
+    pub(crate) fn focused(&self) -> Option<ViewId> {
+        self.keyboard.focus
+    }
+
+    #[inline(always)]
+    pub(crate) fn modifiers(&self) -> winit::keyboard::ModifiersState {
+        self.keyboard.modifiers
+    }
+
+    pub(crate) fn set_modifiers(&mut self, modifiers: winit::keyboard::ModifiersState) {
+        self.keyboard.modifiers = modifiers;
+    }
+
+    pub(crate) fn set_focus(&mut self, focus: Option<ViewId>) {
+        if self.keyboard.focus != focus {
+            self.keyboard.focus = focus;
+            Self::toggle_dirty();
+        }
+    }
+
+    pub(crate) fn handle_keyboard_input(&mut self, root_id: &ViewId, event: &winit::event::KeyEvent) {
+        let event = KeyEvent::new(event, self.keyboard.modifiers);
+        self.handle_key(root_id, event);
+    }
+
+    pub(crate) fn handle_ime(&mut self, root_id: &ViewId, ime: winit::event::Ime) {
+        if let winit::event::Ime::Commit(text) = ime {
+            let event = KeyEvent::ime_commit(text, self.keyboard.modifiers);
+            self.handle_key(root_id, event);
+        }
+    }
+
+    /// Tab & Shift+Tab move the focus, the other keys go to the focused widget
+    fn handle_key(&mut self, root_id: &ViewId, event: KeyEvent) {
+        if event.is_tab() {
+            if event.pressed {
+                self.move_focus(root_id, event.modifiers.shift_key());
+            }
+            return;
+        }
+
+        if let Some(focus) = self.keyboard.focus {
+            CALLBACKS.with(|cb| {
+                if let Some(callbacks) = cb.borrow_mut().get_mut(&focus)
+                && let Some(callback) = callbacks.get_mut(&WidgetEvent::Input)
+                {
+                    callback.call_with_key(&event);
+                }
+            });
+        }
+    }
+
+    /// Move the focus to the next focusable widget in tree order, wrapping around at the end
+    fn move_focus(&mut self, root_id: &ViewId, backward: bool) {
+        // a modal popup keeps the focus inside of it
+        let layers = self.input_layers(root_id);
+        let focus_root = layers.last().unwrap_or(root_id);
+        let focusable = VIEW_STORAGE.with(|s| layer_members(&s.tree.borrow(), focus_root))
+            .into_iter()
+            .filter(is_focusable)
+            .collect::<Vec<_>>();
+
+        if focusable.is_empty() { return }
+
+        let len = focusable.len();
+        let next = match self.keyboard.focus.and_then(|id| focusable.iter().position(|f| f == &id)) {
+            Some(i) if backward => (i + len - 1) % len,
+            Some(i) => (i + 1) % len,
+            None if backward => len - 1,
+            None => 0,
+        };
+
+        self.set_focus(Some(focusable[next]));
+    }
+}
+
+fn is_focusable(id: &ViewId) -> bool {
+    VIEW_STORAGE.with(|s| {
+        s.tree
+            .borrow()
+            .get(id)
+            .is_some_and(|state| state.focusable)
+    })
+}
+
+// #########################################################
+// #                                                       #
+// #                         Render                        #
+// #                                                       #
+// #########################################################
+
+impl Context {
+    pub(crate) fn prepare_data(&mut self, root_id: ViewId, mut scene: Scene<'_>) {
+        let updated = update_views();
+        self.forget_removed_views();
+        self.update_overlays();
+
+        // the content of a scroll view follows the offset written through its signal
+        updated
+            .into_iter()
+            .filter(|id| VIEW_STORAGE.with(|s| {
+                s.tree.borrow().get(id).is_some_and(|state| state.scroll.is_some())
+            }))
+            .for_each(|id| LayoutContext::new(id).calculate());
+
+        self.draw_children(&root_id, &mut scene);
+
+        // the overlay is drawn over the main tree, and the tooltip over everything
+        let overlays = self.overlays
+            .iter()
+            .filter(|overlay| overlay.window == root_id)
+            .map(|overlay| overlay.id)
+            .collect::<Vec<_>>();
+        overlays.iter().for_each(|id| self.draw_recursive(id, &mut scene));
+
+        if let Some(tooltip) = self.tooltip.filter(|tooltip| tooltip.window == root_id) {
+            self.draw_recursive(&tooltip.id, &mut scene);
+        }
+    }
+
+    fn draw_children(&self, parent: &ViewId, scene: &mut Scene<'_>) {
+        let raised = self.raised();
+        let children = VIEW_STORAGE.with(|s| paint_order(&s.tree.borrow(), parent, raised));
+        children
+            .iter()
+            .for_each(|child| self.draw_recursive(child, scene));
+    }
+
+    /// Draw the widget, then its children clipped to its shape if needed
+    fn draw_recursive(&self, view_id: &ViewId, scene: &mut Scene<'_>) {
+        let (clip, thumbs) = VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            let state = tree.get(view_id).unwrap();
+            self.draw_widget(view_id, state, scene);
+
+            let clip = state.clips_children().then(|| (state.shape_rect(), state.clip_corners()));
+            let thumbs = state.scroll
+                .as_ref()
+                .filter(|scroll| scroll.scrollbar)
+                .map(|scroll| scroll.thumbs(state.rect));
+
+            (clip, thumbs)
+        });
+
+        match clip {
+            Some((rect, corners)) => {
+                scene.push_rounded_clip(rect, corners);
+                self.draw_children(view_id, scene);
+                scene.pop_clip();
+            },
+            None => self.draw_children(view_id, scene),
+        }
+
+        thumbs
+            .iter()
+            .flatten()
+            .flatten()
+            .for_each(|thumb| fill_rect(scene, *thumb, &SCROLLBAR_COLOR));
+    }
+
+    fn draw_widget(&self, view_id: &ViewId, state: &WidgetState, scene: &mut Scene<'_>) {
+        let size = scene.size();
+
+        let is_hovered = self.cursor.hover.curr.is_some_and(|id| &id == view_id);
+        let is_clicked = is_hovered && self.cursor.is_clicking();
+
+        let background = state.background_paint(is_hovered, is_clicked);
+        let border = state.border_color.as_paint_ref();
+        let shape = state.shape;
+        let corners = state.corner_radius;
+        let transform = state.get_transform(size);
+        let border_width = state.drawn_border_width() / size.width;
+
+        scene.draw(transform, background, border, border_width, shape, corners);
+
+        if let Some(text) = state.text.as_ref() {
+            let origin = state.rect.vec2f() + Vec2f::new(state.padding.left - text.scroll, state.padding.top);
+            let clip = state.text_clip();
+            if let Some(clip) = clip {
+                scene.push_clip(clip);
+            }
+
+            let selection = self.keyboard.focus
+                .is_some_and(|id| &id == view_id)
+                .then(|| text.selection_rects())
+                .flatten();
+
+            if let Some((highlight, _)) = selection
+            && highlight.width > 0.0
+            {
+                let rect = Rect::from_vec2f_size(origin + highlight.vec2f(), highlight.size());
+                fill_rect(scene, rect, &SELECTION_COLOR);
+            }
+
+            text.layout()
+                .iter()
+                .for_each(|glyph| {
+                    let rect = Rect::from_vec2f_size(origin + glyph.rect.vec2f(), glyph.rect.size());
+                    let transform = get_transform(rect, 0.0, size);
+                    let mask = PaintRef::Glyph(glyph.image.downgrade(), &text.color);
+                    let border = PaintRef::Color(&Rgba::TRANSPARENT);
+
+                    scene.draw(transform, mask, border, 0.0, Shape::Rect, CornerRadius::splat(0.0));
+                });
+
+            if let Some((_, caret)) = selection {
+                let rect = Rect::from_vec2f_size(origin + caret.vec2f(), caret.size());
+                fill_rect(scene, rect, &text.color);
+            }
+
+            if clip.is_some() {
+                scene.pop_clip();
+            }
+        }
+    }
+}
+
+/// Draw a plain rectangle, without border
+fn fill_rect(scene: &mut Scene<'_>, rect: Rect, color: &Rgba<u8>) {
+    let transform = get_transform(rect, 0.0, scene.size());
+    let color = PaintRef::Color(color);
+    let border = PaintRef::Color(&Rgba::TRANSPARENT);
+    scene.draw(transform, color, border, 0.0, Shape::Rect, CornerRadius::splat(0.0));
+}
+
+#[cfg(test)]
+mod context_test {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use aplite_future::Executor;
+    use aplite_types::Size;
+    use winit::keyboard::{Key, ModifiersState, NamedKey};
+
+    use crate::context::layout::Justify;
+    use crate::view::{View, run_effects};
+    use crate::widget::{Button, VStack, Widget, WidgetExt, popup, scroll_view};
+    use super::*;
+
+    fn key(key: Key, modifiers: ModifiersState) -> KeyEvent {
+        KeyEvent {
+            key,
+            text: None,
+            modifiers,
+            pressed: true,
+            repeat: false,
+        }
+    }
+
+    fn tab(shift: bool) -> KeyEvent {
+        let modifiers = if shift { ModifiersState::SHIFT } else { ModifiersState::empty() };
+        key(Key::Named(NamedKey::Tab), modifiers)
+    }
+
+    #[test]
+    fn focus_traversal_and_dispatch() {
+        let root_id = View::window(Size::new(100., 100.)).node.id();
+        let received = Rc::new(RefCell::new(Vec::new()));
+
+        let first = Button::new();
+        let plain = Button::new();
+        let rc = Rc::clone(&received);
+        let last = Button::new().on(WidgetEvent::Input, move |event: &KeyEvent| rc.borrow_mut().push(event.key.clone()));
+        let (first_id, last_id) = (first.id(), last.id());
+
+        VIEW_STORAGE.with(|s| {
+            s.append_child(&root_id, first.focusable(true));
+            s.append_child(&root_id, plain);
+            s.append_child(&root_id, last);
+        });
+
+        let mut cx = Context::new();
+        assert_eq!(cx.focused(), None);
+
+        cx.handle_key(&root_id, tab(false));
+        assert_eq!(cx.focused(), Some(first_id));
+        cx.handle_key(&root_id, tab(false));
+        assert_eq!(cx.focused(), Some(last_id));
+        cx.handle_key(&root_id, tab(false));
+        assert_eq!(cx.focused(), Some(first_id));
+        cx.handle_key(&root_id, tab(true));
+        assert_eq!(cx.focused(), Some(last_id));
+
+        // the release of the tab neither moves the focus, nor goes to the focused widget
+        cx.handle_key(&root_id, KeyEvent { pressed: false, ..tab(false) });
+        assert_eq!(cx.focused(), Some(last_id));
+        assert!(received.borrow().is_empty());
+
+        cx.handle_key(&root_id, key(Key::Character("a".into()), ModifiersState::empty()));
+        assert_eq!(received.borrow().as_slice(), &[Key::Character("a".into())]);
+    }
+
+    #[test]
+    fn wheel_scrolls_and_clips() {
+        aplite_future::Executor::init();
+
+        let root_id = View::window(Size::new(400., 400.)).node.id();
+        let offset = Signal::new(Vec2f::default());
+
+        let buttons = [(); 3].map(|_| Button::new().size((100, 100)));
+        let ids = buttons.each_ref().map(Widget::id);
+        let content = buttons
+            .into_iter()
+            .fold(VStack::new(), |stack, button| stack.child(button));
+        let scroll = scroll_view(content).size((100, 150)).offset(offset);
+        let scroll_id = scroll.id();
+
+        VIEW_STORAGE.with(|s| s.append_child(&root_id, scroll));
+
+        let mut cx = Context::new();
+        cx.layout_the_whole_window(&root_id);
+
+        let y_of = |id: &ViewId| VIEW_STORAGE.with(|s| s.tree.borrow().get(id).unwrap().rect.y);
+        assert_eq!(ids.each_ref().map(y_of), [0., 100., 200.]);
+
+        // the last button is outside of the viewport, and can't be hovered
+        cx.handle_mouse_move(&root_id, (200., 250.));
+        assert_eq!(cx.cursor.hover.curr, None);
+
+        // the offset is clamped to the content, and written back to the signal
+        cx.handle_mouse_move(&root_id, (200., 60.));
+        cx.handle_scroll(&root_id, Vec2f::new(0., 1000.));
+        let max = VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            let state = tree.get(&scroll_id).unwrap();
+            state.scroll.as_ref().unwrap().max_offset(state.rect.size())
+        });
+        assert_eq!(max.y, 150.);
+        assert_eq!(offset.get_untracked(), max);
+        assert_eq!(ids.each_ref().map(y_of), [-max.y, 100. - max.y, 200. - max.y]);
+        assert_eq!(cx.cursor.hover.curr, Some(ids[2]));
+
+        cx.handle_scroll(&root_id, Vec2f::new(0., -60.));
+        assert_eq!(offset.get_untracked(), Vec2f::new(0., max.y - 60.));
+        assert_eq!(y_of(&ids[0]), 60. - max.y);
+    }
+
+    #[test]
+    fn resize_reflows_the_window() {
+        let root_id = View::window(Size::new(400., 400.)).node.id();
+        let content = VStack::new()
+            .child(Button::new().size((100, 50)))
+            .child(Button::new().size((100, 50)));
+        let content_id = content.id();
+
+        VIEW_STORAGE.with(|s| {
+            s.append_child(&root_id, content);
+            s.tree.borrow_mut().get_mut(&root_id).unwrap().set_justify(Justify::Center);
+        });
+        let rect_of = |id: &ViewId| VIEW_STORAGE.with(|s| s.tree.borrow().get(id).unwrap().rect);
+
+        // measuring again gives the same layout
+        let mut cx = Context::new();
+        cx.layout_the_whole_window(&root_id);
+        assert_eq!(rect_of(&content_id), Rect::new(150., 150., 100., 100.));
+        cx.layout_the_whole_window(&root_id);
+        assert_eq!(rect_of(&content_id), Rect::new(150., 150., 100., 100.));
+
+        cx.resize_window(&root_id, Size::new(600., 300.));
+        assert_eq!(rect_of(&root_id), Rect::new(0., 0., 600., 300.));
+        assert_eq!(rect_of(&content_id), Rect::new(250., 100., 100., 100.));
+    }
+
+    #[test]
+    fn clipped_children_are_not_hovered() {
+        let root_id = View::window(Size::new(400., 400.)).node.id();
+
+        let button = Button::new().size((100, 100));
+        let button_id = button.id();
+        let container = VStack::new().child(button).clip(true);
+        let container_id = container.id();
+
+        VIEW_STORAGE.with(|s| {
+            s.append_child(&root_id, container);
+            s.tree.borrow_mut().get_mut(&container_id).unwrap().set_size((50, 50));
+        });
+
+        let mut cx = Context::new();
+        cx.handle_mouse_move(&root_id, (25., 25.));
+        assert_eq!(cx.cursor.hover.curr, Some(button_id));
+
+        // inside the button, but outside of the container
+        cx.handle_mouse_move(&root_id, (75., 75.));
+        assert_eq!(cx.cursor.hover.curr, None);
+    }
+
+    #[test]
+    fn hit_test_matches_the_drawn_shape() {
+        let circle = WidgetState::new()
+            .with_size((100, 100))
+            .with_shape(Shape::Circle);
+        assert!(circle.hit_test(Vec2f::new(50., 50.)));
+        assert!(circle.hit_test(Vec2f::new(50., 1.)));
+        assert!(!circle.hit_test(Vec2f::new(5., 5.)));
+
+        // a wide circle keeps to the square in the middle, and clips its children the same way
+        let wide = WidgetState::new()
+            .with_size((200, 100))
+            .with_shape(Shape::Circle);
+        [(100., 50.), (55., 50.), (100., 2.), (20., 50.), (60., 10.), (190., 90.)]
+            .map(|(x, y)| Vec2f::new(x, y))
+            .iter()
+            .for_each(|pos| assert_eq!(wide.hit_test(*pos), is_inside_clip(&wide, *pos), "at {pos:?}"));
+        assert!(wide.hit_test(Vec2f::new(55., 50.)));
+        assert!(!wide.hit_test(Vec2f::new(20., 50.)));
+        assert!(!wide.hit_test(Vec2f::new(60., 10.)));
+
+        // a radius of 100 is half the width
+        let rounded = WidgetState::new()
+            .with_size((100, 100))
+            .with_shape(Shape::RoundedRect)
+            .with_corner_radius(CornerRadius::new(100., 0., 0., 0.));
+        assert!(!rounded.hit_test(Vec2f::new(5., 5.)));
+        assert!(rounded.hit_test(Vec2f::new(95., 5.)));
+        assert!(rounded.hit_test(Vec2f::new(5., 95.)));
+
+        // the border straddles the edge, so half of it is outside of the rect
+        let mut bordered = WidgetState::new()
+            .with_size((100, 100))
+            .with_shape(Shape::Rect);
+        bordered.set_border_width(10.);
+        assert!(bordered.hit_test(Vec2f::new(99., 50.)));
+        assert!(!bordered.hit_test(Vec2f::new(101., 50.)));
+
+        // standing upright around its center at (100, 10)
+        let rotated = WidgetState::new()
+            .with_size((200, 20))
+            .with_shape(Shape::Rect)
+            .with_rotation_deg(90.);
+        assert!(rotated.hit_test(Vec2f::new(100., 80.)));
+        assert!(!rotated.hit_test(Vec2f::new(180., 10.)));
+    }
+
+    #[test]
+    fn topmost_widget_is_hovered() {
+        let root_id = View::window(Size::new(400., 400.)).node.id();
+
+        let below = Button::new().size((100, 100));
+        let below_id = below.id();
+        let above = Button::new().size((50, 50));
+        let above_id = above.id();
+
+        VIEW_STORAGE.with(|s| {
+            s.append_child(&root_id, below);
+            s.append_child(&root_id, above);
+        });
+
+        // both are at the origin, and the last child is painted on top
+        let mut cx = Context::new();
+        cx.handle_mouse_move(&root_id, (25., 25.));
+        assert_eq!(cx.cursor.hover.curr, Some(above_id));
+
+        cx.handle_mouse_move(&root_id, (75., 75.));
+        assert_eq!(cx.cursor.hover.curr, Some(below_id));
+    }
+
+    #[test]
+    fn z_index_orders_painting_and_hover() {
+        let root_id = View::window(Size::new(400., 400.)).node.id();
+
+        let handle = Button::new().size((50, 50)).dragable(true);
+        let handle_id = handle.id();
+        let raised = Button::new().size((100, 100)).z_index(1);
+        let raised_id = raised.id();
+        let above = Button::new().size((50, 50));
+        let above_id = above.id();
+
+        VIEW_STORAGE.with(|s| {
+            s.append_child(&root_id, handle);
+            s.append_child(&root_id, raised);
+            s.append_child(&root_id, above);
+
+            let mut tree = s.tree.borrow_mut();
+            for id in [raised_id, above_id] {
+                let state = tree.get_mut(&id).unwrap();
+                state.rect.set_pos(Vec2f::splat(100.));
+                s.update_bounds(&id, state);
+            }
+        });
+
+        let order = |cx: &Context| VIEW_STORAGE.with(|s| paint_order(&s.tree.borrow(), &root_id, cx.raised()));
+
+        // the raised one is above the later sibling
+        let mut cx = Context::new();
+        assert_eq!(order(&cx), vec![handle_id, above_id, raised_id]);
+        cx.handle_mouse_move(&root_id, (125., 125.));
+        assert_eq!(cx.cursor.hover.curr, Some(raised_id));
+
+        // the dragged widget rises to the front until released
+        cx.handle_mouse_move(&root_id, (25., 25.));
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, Instant::now());
+        cx.handle_mouse_move(&root_id, (125., 125.));
+        assert_eq!(cx.cursor.hover.curr, Some(handle_id));
+        assert_eq!(order(&cx), vec![above_id, raised_id, handle_id]);
+
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, Instant::now());
+        assert_eq!(order(&cx), vec![handle_id, above_id, raised_id]);
+        cx.handle_mouse_move(&root_id, (126., 126.));
+        assert_eq!(cx.cursor.hover.curr, Some(raised_id));
+    }
+
+    /// A window with a grid of `side * side` hoverable buttons, and the positions probed on it
+    fn hover_grid(side: usize, probes: usize) -> (ViewId, Vec<Vec2f>) {
+        let root_id = View::window(Size::new(2000., 2000.)).node.id();
+        VIEW_STORAGE.with(|s| {
+            (0..side * side).for_each(|i| {
+                let button = Button::new().size((30, 30));
+                let id = button.id();
+                s.append_child(&root_id, button);
+
+                let mut tree = s.tree.borrow_mut();
+                let state = tree.get_mut(&id).unwrap();
+                state.rect.set_pos(Vec2f::new((i % side) as f32 * 40., (i / side) as f32 * 40.));
+                s.update_bounds(&id, state);
+            });
+        });
+
+        let positions = (0..probes)
+            .map(|i| Vec2f::new((i * 37 % 2000) as f32, (i * 91 % 2000) as f32))
+            .collect();
+
+        (root_id, positions)
+    }
+
+    /// The topmost hovered widget, found by testing every member of the window
+    fn linear_hover(root_id: &ViewId, pos: Vec2f) -> Option<ViewId> {
+        VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            tree.get_all_members_of(root_id)
+                .into_iter()
+                .rev()
+                .find(|id| {
+                    tree.get(id).is_some_and(|state| state.hoverable && state.hit_test(pos))
+                        && !is_clipped(&tree, id, pos)
                 })
+        })
+    }
+
+    #[test]
+    fn hover_index_matches_linear_scan() {
+        let (root_id, positions) = hover_grid(50, 500);
+        let mut cx = Context::new();
+
+        let hits = positions
+            .iter()
+            .map(|pos| {
+                cx.cursor.hover.pos = *pos;
+                cx.detect_hover(&root_id);
+                assert_eq!(cx.cursor.hover.curr, linear_hover(&root_id, *pos), "at {pos:?}");
+                cx.cursor.hover.curr
+            })
+            .collect::<Vec<_>>();
+
+        // both the widgets & the gaps between them are probed
+        assert!(hits.iter().any(Option::is_some));
+        assert!(hits.iter().any(Option::is_none));
+    }
+
+    /// Run with `cargo test hover_index_benchmark -- --ignored --nocapture`
+    #[test]
+    #[ignore = "benchmark"]
+    fn hover_index_benchmark() {
+        const SIDE: usize = 50;
+        const MOVES: usize = 500;
+
+        let (root_id, positions) = hover_grid(SIDE, MOVES);
+        let mut cx = Context::new();
+
+        let now = Instant::now();
+        positions.iter().for_each(|pos| {
+            cx.cursor.hover.pos = *pos;
+            cx.detect_hover(&root_id);
         });
+        let time_index = now.elapsed();
+
+        let now = Instant::now();
+        positions.iter().for_each(|pos| { linear_hover(&root_id, *pos); });
+        let time_linear = now.elapsed();
+
+        eprintln!("HOVER {} widgets: index: {time_index:?} | linear: {time_linear:?}", SIDE * SIDE);
+    }
+
+    #[test]
+    fn removed_views_are_forgotten() {
+        let root_id = View::window(Size::new(400., 400.)).node.id();
+
+        let button = Button::new().size((100, 100)).focusable(true).on(WidgetEvent::LeftClick, || {});
+        let button_id = button.id();
+        VIEW_STORAGE.with(|s| s.append_child(&root_id, button));
+
+        let mut cx = Context::new();
+        cx.handle_mouse_move(&root_id, (50., 50.));
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, Instant::now());
+        assert_eq!(cx.cursor.hover.curr, Some(button_id));
+        assert_eq!(cx.focused(), Some(button_id));
+
+        VIEW_STORAGE.with(|s| s.remove(&button_id));
+        cx.forget_removed_views();
+        assert_eq!(cx.cursor.hover.curr, None);
+        assert_eq!(cx.focused(), None);
+        assert!(cx.pending_event.is_empty());
+
+        // nothing is left to be hovered or clicked
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, Instant::now());
+        cx.handle_mouse_move(&root_id, (60., 60.));
+        assert_eq!(cx.cursor.hover.curr, None);
+    }
+
+    #[test]
+    fn mouse_events_are_dispatched() {
+        let root_id = View::window(Size::new(400., 400.)).node.id();
+        let received = Rc::new(RefCell::new(Vec::new()));
+
+        let events = [
+            WidgetEvent::HoverEnter,
+            WidgetEvent::HoverLeave,
+            WidgetEvent::LeftClick,
+            WidgetEvent::RightClick,
+            WidgetEvent::MiddleClick,
+            WidgetEvent::DragStart,
+            WidgetEvent::DragMove,
+            WidgetEvent::DragEnd,
+        ];
+        let button = events
+            .into_iter()
+            .fold(Button::new().size((100, 100)), |button, event| {
+                let rc = Rc::clone(&received);
+                button.on(event, move |e: &MouseEvent| rc.borrow_mut().push((e.kind, e.delta)))
+            });
+        VIEW_STORAGE.with(|s| s.append_child(&root_id, button));
+
+        let mut cx = Context::new();
+        let take = || received.borrow_mut().drain(..).collect::<Vec<_>>();
+        let zero = Vec2f::default();
+
+        cx.handle_mouse_move(&root_id, (50., 50.));
+        assert_eq!(take(), [(WidgetEvent::HoverEnter, zero)]);
+
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Right, Instant::now());
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Right, Instant::now());
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Middle, Instant::now());
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Middle, Instant::now());
+        assert_eq!(take(), [(WidgetEvent::RightClick, zero), (WidgetEvent::MiddleClick, zero)]);
+
+        // a small movement is still a click
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, Instant::now());
+        cx.handle_mouse_move(&root_id, (51., 50.));
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, Instant::now());
+        assert_eq!(take(), [(WidgetEvent::LeftClick, zero)]);
+
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, Instant::now());
+        cx.handle_mouse_move(&root_id, (61., 55.));
+        cx.handle_mouse_move(&root_id, (71., 55.));
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, Instant::now());
+        assert_eq!(take(), [
+            (WidgetEvent::DragStart, zero),
+            (WidgetEvent::DragMove, Vec2f::new(10., 5.)),
+            (WidgetEvent::DragMove, Vec2f::new(10., 0.)),
+            (WidgetEvent::DragEnd, zero),
+        ]);
+
+        cx.handle_mouse_move(&root_id, (200., 200.));
+        assert_eq!(take(), [(WidgetEvent::HoverLeave, zero)]);
+    }
+
+    #[test]
+    fn click_propagates_through_ancestors() {
+        let root_id = View::window(Size::new(400., 400.)).node.id();
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let stop_at_button = Rc::new(std::cell::Cell::new(false));
+
+        let (rc, stop) = (Rc::clone(&received), Rc::clone(&stop_at_button));
+        let button = Button::new()
+            .size((100, 100))
+            .on(WidgetEvent::LeftClick, move |e: &MouseEvent| {
+                rc.borrow_mut().push("button");
+                e.set_handled();
+                if stop.get() { e.stop_propagation() }
+            });
+        let button_id = button.id();
+
+        let (rc, capture_rc) = (Rc::clone(&received), Rc::clone(&received));
+        let stack = VStack::new()
+            .child(button)
+            .on(WidgetEvent::LeftClick, move |e: &MouseEvent| {
+                assert!(e.is_handled());
+                assert_eq!(e.target, button_id);
+                rc.borrow_mut().push("stack");
+            })
+            .on_capture(WidgetEvent::LeftClick, move |e: &MouseEvent| {
+                assert!(!e.is_handled());
+                capture_rc.borrow_mut().push("stack capture");
+            });
+        VIEW_STORAGE.with(|s| s.append_child(&root_id, stack));
+
+        let mut cx = Context::new();
+        let mut click = || {
+            cx.handle_mouse_move(&root_id, (50., 50.));
+            cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, Instant::now());
+            cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, Instant::now());
+            received.borrow_mut().drain(..).collect::<Vec<_>>()
+        };
+
+        assert_eq!(click(), ["stack capture", "button", "stack"]);
+
+        stop_at_button.set(true);
+        assert_eq!(click(), ["stack capture", "button"]);
+    }
+
+    #[test]
+    fn cursor_icon_follows_hover_and_drag() {
+        let root_id = View::window(Size::new(400., 400.)).node.id();
+
+        let button = Button::new().size((100, 100));
+        let handle = Button::new()
+            .size((50, 50))
+            .cursor_icon(CursorIcon::Grab)
+            .dragable(true);
+        // without an icon of its own, the child uses the one of the container
+        let child = VStack::new()
+            .size((50, 50))
+            .on(WidgetEvent::HoverEnter, || {});
+        let container = VStack::new()
+            .child(child)
+            .cursor_icon(CursorIcon::ResizeVertical);
+
+        let container_id = container.id();
+        VIEW_STORAGE.with(|s| {
+            s.append_child(&root_id, button);
+            s.append_child(&root_id, handle);
+            s.append_child(&root_id, container);
+            s.tree.borrow_mut().get_mut(&container_id).unwrap().rect.set_pos(Vec2f::new(0., 200.));
+        });
+        LayoutContext::new(container_id).calculate();
+
+        let mut cx = Context::new();
+        cx.handle_mouse_move(&root_id, (75., 75.));
+        assert_eq!(cx.cursor_icon(), CursorIcon::Pointer);
+
+        cx.handle_mouse_move(&root_id, (25., 225.));
+        assert_eq!(cx.cursor_icon(), CursorIcon::ResizeVertical);
+
+        cx.handle_mouse_move(&root_id, (300., 300.));
+        assert_eq!(cx.cursor_icon(), CursorIcon::Default);
+
+        cx.handle_mouse_move(&root_id, (25., 25.));
+        assert_eq!(cx.cursor_icon(), CursorIcon::Grab);
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, Instant::now());
+        cx.handle_mouse_move(&root_id, (35., 35.));
+        assert_eq!(cx.cursor_icon(), CursorIcon::Grabbing);
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, Instant::now());
+        assert_eq!(cx.cursor_icon(), CursorIcon::Grab);
+    }
+
+    #[test]
+    fn tooltip_is_shown_after_the_delay() {
+        Executor::init();
+        let root_id = View::window(Size::new(200., 200.)).node.id();
+
+        let label = Button::new().size((100, 100)).tooltip("hello");
+        let label_id = label.id();
+        let custom = Button::new()
+            .size((100, 50))
+            .tooltip(|| Button::new().size((80, 40)));
+
+        let custom_id = custom.id();
+        VIEW_STORAGE.with(|s| {
+            s.append_child(&root_id, label);
+            s.append_child(&root_id, custom);
+            let mut tree = s.tree.borrow_mut();
+            let state = tree.get_mut(&custom_id).unwrap();
+            state.rect.set_pos(Vec2f::new(100., 150.));
+            s.update_bounds(&custom_id, state);
+        });
+
+        let mut cx = Context::new();
+        let mut settings = ClickSettings {
+            tooltip_delay: std::time::Duration::from_secs(3600),
+            ..Default::default()
+        };
+        cx.set_click_settings(settings);
+        cx.handle_mouse_move(&root_id, (50., 50.));
+        cx.handle_tooltip();
+        assert!(cx.tooltip.is_none());
+        assert!(cx.tooltip_deadline().is_some());
+
+        settings.tooltip_delay = std::time::Duration::ZERO;
+        cx.set_click_settings(settings);
+        cx.handle_tooltip();
+        let tooltip = cx.tooltip.unwrap();
+        assert_eq!(tooltip.owner, label_id);
+        VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            assert_eq!(tree.get(&tooltip.id).unwrap().rect.vec2f(), Vec2f::new(62., 70.));
+            assert!(!tree.is_member_of(&tooltip.id, &root_id));
+        });
+
+        // a click hides it until the cursor leaves
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, Instant::now());
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, Instant::now());
+        cx.handle_tooltip();
+        assert!(cx.tooltip.is_none());
+        assert!(VIEW_STORAGE.with(|s| !s.tree.borrow().contains(&tooltip.id)));
+
+        // moved to the left & above the cursor, to stay inside the window
+        cx.handle_mouse_move(&root_id, (190., 190.));
+        cx.handle_tooltip();
+        let tooltip = cx.tooltip.unwrap();
+        assert_eq!(tooltip.owner, custom_id);
+        let rect = VIEW_STORAGE.with(|s| s.tree.borrow().get(&tooltip.id).unwrap().rect);
+        assert_eq!(rect, Rect::new(120., 145., 80., 40.));
+
+        cx.handle_mouse_move(&root_id, (150., 50.));
+        assert!(cx.tooltip.is_none());
+        assert!(VIEW_STORAGE.with(|s| !s.tree.borrow().contains(&tooltip.id)));
+    }
+
+    /// Run the effects, then open & close the popups as a redraw does
+    fn update_overlays(cx: &mut Context) {
+        run_effects();
+        cx.update_overlays();
+    }
+
+    #[test]
+    fn popup_overlay() {
+        Executor::init();
+        let root_id = View::window(Size::new(300., 300.)).node.id();
+
+        let open = Signal::new(false);
+        let menu = Button::new().size((120, 60));
+        let menu_id = menu.id();
+        let menu = Rc::new(RefCell::new(Some(menu)));
+        // the anchor is clipped by its container, but the popup escapes it
+        let anchor = Button::new()
+            .size((100, 40))
+            .popup(popup(move || open.get(), move || menu.borrow_mut().take().unwrap())
+                .on_dismiss(move || open.set(false)));
+        let anchor_id = anchor.id();
+        let container = VStack::new().child(anchor).clip(true);
+        let container_id = container.id();
+
+        let dialog_open = Signal::new(true);
+        let dialog = Button::new()
+            .size((10, 10))
+            .popup(popup(move || dialog_open.get(), || Button::new().size((100, 100)))
+                .placement(Placement::Center)
+                .modal(true)
+                .dismiss_on_outside_click(false));
+        let dialog_anchor = dialog.id();
+
+        VIEW_STORAGE.with(|s| {
+            s.append_child(&root_id, container);
+            s.append_child(&root_id, dialog);
+            let mut tree = s.tree.borrow_mut();
+            let state = tree.get_mut(&container_id).unwrap();
+            state.set_size((100, 40));
+            state.rect.set_pos(Vec2f::new(0., 250.));
+            tree.get_mut(&dialog_anchor).unwrap().rect.set_pos(Vec2f::new(290., 0.));
+        });
+        LayoutContext::new(container_id).calculate();
+
+        // the modal dialog blocks everything underneath
+        let mut cx = Context::new();
+        update_overlays(&mut cx);
+        assert_eq!(cx.overlays.len(), 1);
+        cx.handle_mouse_move(&root_id, (50., 270.));
+        assert_eq!(cx.cursor.hover.curr, None);
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, Instant::now());
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, Instant::now());
+        assert_eq!(cx.overlays.len(), 1);
+
+        dialog_open.set(false);
+        update_overlays(&mut cx);
+        assert_eq!(cx.overlays.len(), 0);
+        cx.handle_mouse_move(&root_id, (50., 270.));
+        assert_eq!(cx.cursor.hover.curr, Some(anchor_id));
+
+        // no room below the anchor, so it's flipped above
+        open.set(true);
+        update_overlays(&mut cx);
+        assert_eq!(cx.overlays.len(), 1);
+        let rect = VIEW_STORAGE.with(|s| s.tree.borrow().get(&menu_id).unwrap().rect);
+        assert_eq!(rect, Rect::new(0., 186., 120., 60.));
+
+        cx.handle_mouse_move(&root_id, (110., 200.));
+        assert_eq!(cx.cursor.hover.curr, Some(menu_id));
+
+        // a click on the anchor is left to the anchor
+        cx.handle_mouse_move(&root_id, (50., 270.));
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, Instant::now());
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, Instant::now());
+        assert_eq!(cx.overlays.len(), 1);
+
+        cx.handle_mouse_move(&root_id, (250., 50.));
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, Instant::now());
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, Instant::now());
+        assert!(cx.overlays.is_empty());
+        assert!(!open.get_untracked());
+        assert!(VIEW_STORAGE.with(|s| !s.tree.borrow().contains(&menu_id)));
+    }
+
+    #[test]
+    fn double_click_and_long_press() {
+        use std::time::Duration;
+
+        let root_id = View::window(Size::new(400., 400.)).node.id();
+        let received = Rc::new(RefCell::new(Vec::new()));
+
+        let events = [WidgetEvent::LeftClick, WidgetEvent::DoubleClick, WidgetEvent::LongPress];
+        let button = events
+            .into_iter()
+            .fold(Button::new().size((100, 100)), |button, event| {
+                let rc = Rc::clone(&received);
+                button.on(event, move |e: &MouseEvent| rc.borrow_mut().push((e.kind, e.click_count)))
+            });
+        VIEW_STORAGE.with(|s| s.append_child(&root_id, button));
+
+        let mut cx = Context::new();
+        cx.set_click_settings(ClickSettings {
+            double_click_interval: Duration::from_millis(100),
+            double_click_distance: 4.0,
+            long_press_duration: Duration::from_millis(30),
+            ..Default::default()
+        });
+        let take = || received.borrow_mut().drain(..).collect::<Vec<_>>();
+        let start = Instant::now();
+        let at = |millis: u64| start + Duration::from_millis(millis);
+        let click = |cx: &mut Context, pos: (f32, f32), millis: u64| {
+            cx.handle_mouse_move(&root_id, pos);
+            cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, at(millis));
+            cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, at(millis + 10));
+        };
+
+        click(&mut cx, (50., 50.), 0);
+        click(&mut cx, (51., 50.), 50);
+        assert_eq!(take(), [
+            (WidgetEvent::LeftClick, 1),
+            (WidgetEvent::LeftClick, 2),
+            (WidgetEvent::DoubleClick, 2),
+        ]);
+
+        // too slow, and then too far
+        click(&mut cx, (50., 50.), 200);
+        click(&mut cx, (60., 50.), 250);
+        assert_eq!(take(), [(WidgetEvent::LeftClick, 1), (WidgetEvent::LeftClick, 1)]);
+
+        // the long press fires while the button is held, and replaces the click
+        cx.handle_click(&root_id, MouseAction::Pressed, MouseButton::Left, at(400));
+        cx.handle_long_press(at(420));
+        assert!(take().is_empty());
+        assert!(cx.long_press_deadline().is_some());
+
+        cx.handle_long_press(at(430));
+        cx.handle_long_press(at(440));
+        assert_eq!(take(), [(WidgetEvent::LongPress, 1)]);
+        assert!(cx.long_press_deadline().is_none());
+
+        cx.handle_click(&root_id, MouseAction::Released, MouseButton::Left, at(450));
+        assert!(take().is_empty());
     }
 }
+
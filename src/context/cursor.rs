@@ -1,6 +1,15 @@
+use std::cell::Cell;
+use std::time::{Duration, Instant};
+
 use aplite_types::Vec2f;
+use winit::keyboard::ModifiersState;
 
 use crate::view::ViewId;
+use crate::widget::WidgetEvent;
+
+/// The distance in logical pixels the cursor has to move while pressed, before a drag starts.
+/// Smaller movements are still a click
+const DRAG_THRESHOLD: f32 = 3.0;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum MouseAction {
@@ -40,6 +49,46 @@ impl From<winit::event::MouseButton> for MouseButton {
     }
 }
 
+/// The icon of the mouse cursor while hovering a widget
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CursorIcon {
+    #[default]
+    Default,
+    Pointer,
+    /// A draggable widget, replaced with [`Grabbing`](CursorIcon::Grabbing) during the drag
+    Grab,
+    Grabbing,
+    Text,
+    NotAllowed,
+    /// Resize to the left or the right
+    ResizeHorizontal,
+    /// Resize to the top or the bottom
+    ResizeVertical,
+    /// Resize from the top left or the bottom right corner
+    ResizeNwse,
+    /// Resize from the top right or the bottom left corner
+    ResizeNesw,
+    Move,
+}
+
+impl From<CursorIcon> for winit::window::CursorIcon {
+    fn from(value: CursorIcon) -> Self {
+        match value {
+            CursorIcon::Default => Self::Default,
+            CursorIcon::Pointer => Self::Pointer,
+            CursorIcon::Grab => Self::Grab,
+            CursorIcon::Grabbing => Self::Grabbing,
+            CursorIcon::Text => Self::Text,
+            CursorIcon::NotAllowed => Self::NotAllowed,
+            CursorIcon::ResizeHorizontal => Self::EwResize,
+            CursorIcon::ResizeVertical => Self::NsResize,
+            CursorIcon::ResizeNwse => Self::NwseResize,
+            CursorIcon::ResizeNesw => Self::NeswResize,
+            CursorIcon::Move => Self::Move,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct MouseState {
     pub action: MouseAction,
@@ -50,6 +99,40 @@ pub struct MouseState {
 pub struct MouseClick {
     pub pos: Vec2f,
     pub offset: Vec2f,
+    /// the cursor position of the last drag event
+    pub drag: Vec2f,
+}
+
+/// When the button was pressed
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MousePress {
+    pub at: Instant,
+    pub pos: Vec2f,
+    pub button: MouseButton,
+    pub target: Option<ViewId>,
+}
+
+/// How the presses are grouped into double clicks, how long a press has to be held to be a long press,
+/// and how long the cursor has to rest on a widget to show its tooltip
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ClickSettings {
+    /// The maximum time between two presses of a double click
+    pub double_click_interval: Duration,
+    /// The maximum distance in logical pixels between two presses of a double click
+    pub double_click_distance: f32,
+    pub long_press_duration: Duration,
+    pub tooltip_delay: Duration,
+}
+
+impl Default for ClickSettings {
+    fn default() -> Self {
+        Self {
+            double_click_interval: Duration::from_millis(500),
+            double_click_distance: 4.0,
+            long_press_duration: Duration::from_millis(500),
+            tooltip_delay: Duration::from_millis(600),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -57,6 +140,8 @@ pub struct MouseHover {
     pub pos: Vec2f,
     pub curr: Option<ViewId>,
     pub prev: Option<ViewId>,
+    /// When the current widget started being hovered
+    pub since: Instant,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -64,7 +149,12 @@ pub struct Cursor {
     pub hover: MouseHover,
     pub state: MouseState,
     pub click: MouseClick,
-    pub timer: std::time::Duration,
+    pub settings: ClickSettings,
+    /// The current press, or the last one once released
+    pub press: Option<MousePress>,
+    /// The number of presses in a row, each within the double click interval & distance of the previous one
+    pub click_count: u32,
+    pub is_long_pressed: bool,
     pub is_dragging: bool,
 }
 
@@ -75,6 +165,7 @@ impl Default for Cursor {
                 pos: Vec2f::default(),
                 curr: None,
                 prev: None,
+                since: Instant::now(),
             },
             state: MouseState {
                 action: MouseAction::Released,
@@ -83,8 +174,12 @@ impl Default for Cursor {
             click: MouseClick {
                 pos: Vec2f::default(),
                 offset: Vec2f::default(),
+                drag: Vec2f::default(),
             },
-            timer: std::time::Duration::from_millis(0),
+            settings: ClickSettings::default(),
+            press: None,
+            click_count: 0,
+            is_long_pressed: false,
             is_dragging: false,
         }
     }
@@ -99,37 +194,114 @@ impl Cursor {
         self.state = MouseState { action, button };
     }
 
-    pub(crate) fn set_click_state(&mut self, action: MouseAction, button: MouseButton) {
+    pub(crate) fn set_click_state(&mut self, action: MouseAction, button: MouseButton, at: Instant) {
         self.set_state(action, button);
 
-        let start = std::time::Instant::now();
-        match (self.state.action, self.state.button) {
-            (MouseAction::Pressed, MouseButton::Left) => {
-                self.click.pos = self.hover.pos;
-            },
-            (MouseAction::Released, MouseButton::Left) => {
-                self.timer = start.elapsed();
-            },
-            _ => {}
+        if action == MouseAction::Pressed {
+            self.click.pos = self.hover.pos;
+            self.click.drag = self.hover.pos;
+
+            let press = MousePress {
+                at,
+                pos: self.hover.pos,
+                button,
+                target: self.hover.curr,
+            };
+            let repeated = self.press.is_some_and(|prev| {
+                prev.button == press.button
+                    && prev.target == press.target
+                    && press.at - prev.at <= self.settings.double_click_interval
+                    && press.pos.dist_euclid(prev.pos) <= self.settings.double_click_distance
+            });
+
+            self.click_count = if repeated { self.click_count + 1 } else { 1 };
+            self.press = Some(press);
+            self.is_long_pressed = false;
+        }
+    }
+
+    /// When the current press becomes a long press, if it still can
+    pub(crate) fn long_press_deadline(&self) -> Option<Instant> {
+        self.press
+            .filter(|press| {
+                self.is_clicking()
+                    && press.button == MouseButton::Left
+                    && press.target.is_some()
+                    && !self.is_long_pressed
+                    && !self.is_dragging
+            })
+            .map(|press| press.at + self.settings.long_press_duration)
+    }
+
+    /// The click event of the current button, if it has one
+    pub(crate) fn click_event(&self) -> Option<WidgetEvent> {
+        match self.state.button {
+            MouseButton::Left => Some(WidgetEvent::LeftClick),
+            MouseButton::Right => Some(WidgetEvent::RightClick),
+            MouseButton::Middle => Some(WidgetEvent::MiddleClick),
+            _ => None,
         }
     }
 
+    /// The movement since the last drag event, which becomes the current position
+    pub(crate) fn take_drag_delta(&mut self) -> Vec2f {
+        let delta = self.hover.pos - self.click.drag;
+        self.click.drag = self.hover.pos;
+        delta
+    }
+
+    /// Whether the pressed widget has been moved far enough to start a drag
     pub(crate) fn is_dragging(&self, hover_id: &ViewId) -> bool {
         self.is_clicking()
             && self.hover.curr.is_some_and(|id| &id == hover_id)
-            && self.hover.pos != self.click.pos
+            && self.hover.pos.dist_euclid(self.click.pos) >= DRAG_THRESHOLD
     }
 
-    pub(crate) fn is_hovering_same_obj(&self) -> bool {
-        self.hover.curr == self.hover.prev && self.hover.curr.is_some()
+    pub(crate) fn is_clicking(&self) -> bool {
+        matches!(self.state.action, MouseAction::Pressed)
     }
+}
+
+/// The payload of the mouse events, see [`WidgetEvent`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct MouseEvent {
+    pub kind: WidgetEvent,
+    pub button: MouseButton,
+    /// The widget under the cursor, which the event is sent to
+    pub target: ViewId,
+    /// The widget whose callback is running, either the target or one of its ancestors
+    pub current: ViewId,
+    /// The cursor position in the window
+    pub pos: Vec2f,
+    /// The cursor position relative to the current widget's top left corner
+    pub local: Vec2f,
+    /// The movement since the previous drag event, zero for the other events
+    pub delta: Vec2f,
+    /// The movement since the button was pressed, zero for the hover events
+    pub total_delta: Vec2f,
+    /// The number of presses in a row, see [`ClickSettings`]
+    pub click_count: u32,
+    pub modifiers: ModifiersState,
+    pub(crate) stopped: Cell<bool>,
+    pub(crate) handled: Cell<bool>,
+}
 
-    pub(crate) fn is_idling(&self) -> bool {
-        self.is_hovering_same_obj() && !self.is_clicking()
-        || self.hover.curr.is_none() && self.hover.prev.is_none()
+impl MouseEvent {
+    /// The callbacks of the next widgets on the propagation path won't run
+    pub fn stop_propagation(&self) {
+        self.stopped.set(true);
     }
 
-    pub(crate) fn is_clicking(&self) -> bool {
-        matches!(self.state.action, MouseAction::Pressed)
+    pub fn is_propagation_stopped(&self) -> bool {
+        self.stopped.get()
+    }
+
+    /// Let the next widgets know the event has been handled. The event keeps propagating
+    pub fn set_handled(&self) {
+        self.handled.set(true);
+    }
+
+    pub fn is_handled(&self) -> bool {
+        self.handled.get()
     }
 }
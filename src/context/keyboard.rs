@@ -0,0 +1,54 @@
+use winit::keyboard::{Key, ModifiersState, NamedKey};
+use winit::event::ElementState;
+
+use crate::view::ViewId;
+
+/// A key press or release, delivered to the focused widget via [`WidgetEvent::Input`](crate::widget::WidgetEvent::Input)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyEvent {
+    pub key: Key,
+    /// The text produced by the key, or committed by the IME
+    pub text: Option<String>,
+    pub modifiers: ModifiersState,
+    pub pressed: bool,
+    pub repeat: bool,
+}
+
+impl KeyEvent {
+    pub(crate) fn new(event: &winit::event::KeyEvent, modifiers: ModifiersState) -> Self {
+        Self {
+            key: event.logical_key.clone(),
+            text: event.text.as_ref().map(|text| text.to_string()),
+            modifiers,
+            pressed: event.state == ElementState::Pressed,
+            repeat: event.repeat,
+        }
+    }
+
+    pub(crate) fn ime_commit(text: String, modifiers: ModifiersState) -> Self {
+        Self {
+            key: Key::Unidentified(winit::keyboard::NativeKey::Unidentified),
+            text: Some(text),
+            modifiers,
+            pressed: true,
+            repeat: false,
+        }
+    }
+
+    /// Both the press & the release, so the focused widget gets neither
+    pub(crate) fn is_tab(&self) -> bool {
+        self.key == Key::Named(NamedKey::Tab)
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct Keyboard {
+    pub focus: Option<ViewId>,
+    pub modifiers: ModifiersState,
+}
+
+impl Keyboard {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+}
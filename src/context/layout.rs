@@ -1,7 +1,10 @@
-use aplite_types::{Rect, Size, Vec2f};
+use std::ops::Range;
+
+use aplite_types::{Length, Rect, Size, Vec2f};
 
 use crate::state::{AspectRatio, WidgetState};
 use crate::view::{ViewId, VIEW_STORAGE};
+use crate::widget::{GridCell, GridState};
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AlignH {
@@ -9,6 +12,8 @@ pub enum AlignH {
     Left,
     Center,
     Right,
+    /// Fills the width of the parent on the cross axis of a stack, or the cell of a grid
+    Stretch,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -17,6 +22,26 @@ pub enum AlignV {
     Top,
     Middle,
     Bottom,
+    /// Fills the height of the parent on the cross axis of a stack, or the cell of a grid
+    Stretch,
+}
+
+/// How the free space on the main axis of a stack is spread around the children
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Justify {
+    /// Packed at the start
+    #[default]
+    Start,
+    /// Packed in the middle
+    Center,
+    /// Packed at the end
+    End,
+    /// The first & last child are at the edges, with the same space between the others
+    SpaceBetween,
+    /// The same space on both sides of each child, so the edges get half of it
+    SpaceAround,
+    /// The same space between the children & the edges
+    SpaceEvenly,
 }
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,19 +59,49 @@ pub struct Padding {
     pub right: f32,
 }
 
+/// The space a widget keeps around itself inside its parent, which may be relative to its content box.
+/// An auto side takes the free space, so a widget with auto on both sides is centered.
+/// In a grid the auto sides are zero, and the cell aligns the widget.
+/// The parent isn't known when measuring, so a relative side doesn't grow the parent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Margin {
+    pub top: Length,
+    pub bottom: Length,
+    pub left: Length,
+    pub right: Length,
+}
+
+/// The margin of a child along one axis of its parent
+#[derive(Debug, Clone, Copy)]
+struct AxisMargin {
+    start: f32,
+    end: f32,
+    start_auto: bool,
+    end_auto: bool,
+}
+
 #[derive(Debug)]
 pub(crate) struct Rules {
     rect: Rect,
     orientation: Orientation,
     align_h: AlignH,
     align_v: AlignV,
+    justify: Justify,
     padding: Padding,
     spacing: f32,
+    wrap: bool,
+    line_spacing: f32,
+    grid: Option<GridState>,
 }
 
 pub(crate) struct LayoutContext {
     entity: ViewId,
-    next_pos: Vec2f,
+    /// the position of the next child on the main axis
+    next_pos: f32,
+    /// the spacing & the space added by the justification
+    gap: f32,
+    /// the space taken by each auto margin on the main axis
+    auto_margin: f32,
     rules: Rules,
 }
 
@@ -60,6 +115,24 @@ impl Orientation {
     }
 }
 
+impl Justify {
+    /// The space before the first child, and the space added between the children.
+    /// Overflowing children are packed at the start, unless centered or packed at the end
+    fn distribute(&self, free: f32, len: f32) -> (f32, f32) {
+        let spread = free.max(0.);
+        match self {
+            Self::Start => (0., 0.),
+            Self::Center => (free / 2., 0.),
+            Self::End => (free, 0.),
+            Self::SpaceBetween if len > 1. => (0., spread / (len - 1.)),
+            Self::SpaceBetween => (0., 0.),
+            Self::SpaceAround if len > 0. => (spread / len / 2., spread / len),
+            Self::SpaceAround => (0., 0.),
+            Self::SpaceEvenly => (spread / (len + 1.), spread / (len + 1.)),
+        }
+    }
+}
+
 impl Padding {
     pub const fn new(top: f32, bottom: f32, left: f32, right: f32) -> Self {
         Self {
@@ -91,68 +164,191 @@ impl Padding {
     }
 }
 
+impl Default for Margin {
+    fn default() -> Self {
+        Self::splat(0.)
+    }
+}
+
+impl Margin {
+    /// In logical pixels
+    pub const fn new(top: f32, bottom: f32, left: f32, right: f32) -> Self {
+        Self {
+            top: Length::Px(top),
+            bottom: Length::Px(bottom),
+            left: Length::Px(left),
+            right: Length::Px(right),
+        }
+    }
+
+    /// In logical pixels
+    pub const fn splat(value: f32) -> Self {
+        Self::new(value, value, value, value)
+    }
+
+    /// Centered on both axes
+    pub const fn auto() -> Self {
+        Self {
+            top: Length::Auto,
+            bottom: Length::Auto,
+            left: Length::Auto,
+            right: Length::Auto,
+        }
+    }
+
+    /// The sides in pixels, given the content box of the parent if known.
+    /// The auto sides are zero, as [`Length::Fill`] is
+    pub(crate) fn resolve(&self, parent: Option<Size>) -> Padding {
+        let side = |length: Length, parent: Option<f32>| match length {
+            Length::Auto | Length::Fill => 0.,
+            length => length.resolve(parent).unwrap_or_default(),
+        };
+        Padding::new(
+            side(self.top, parent.map(|p| p.height)),
+            side(self.bottom, parent.map(|p| p.height)),
+            side(self.left, parent.map(|p| p.width)),
+            side(self.right, parent.map(|p| p.width)),
+        )
+    }
+
+    /// The margins along the main & the cross axis of the parent
+    fn axes(&self, orientation: Orientation, parent: Option<Size>) -> (AxisMargin, AxisMargin) {
+        let px = self.resolve(parent);
+        let auto = |length: Length| matches!(length, Length::Auto | Length::Fill);
+        let vertical = AxisMargin {
+            start: px.top,
+            end: px.bottom,
+            start_auto: auto(self.top),
+            end_auto: auto(self.bottom),
+        };
+        let horizontal = AxisMargin {
+            start: px.left,
+            end: px.right,
+            start_auto: auto(self.left),
+            end_auto: auto(self.right),
+        };
+        match orientation {
+            Orientation::Vertical => (vertical, horizontal),
+            Orientation::Horizontal => (horizontal, vertical),
+        }
+    }
+}
+
+impl AxisMargin {
+    fn total(&self) -> f32 { self.start + self.end }
+
+    fn is_auto(&self) -> bool { self.start_auto || self.end_auto }
+
+    /// The number of auto sides
+    fn autos(&self) -> f32 { (self.start_auto as u8 + self.end_auto as u8) as f32 }
+
+    /// The space before the child, given the space taken by each auto side
+    fn before(&self, auto: f32) -> f32 {
+        if self.start_auto { self.start + auto } else { self.start }
+    }
+
+    /// The space after the child, given the space taken by each auto side
+    fn after(&self, auto: f32) -> f32 {
+        if self.end_auto { self.end + auto } else { self.end }
+    }
+}
+
 impl Rules {
     pub(crate) fn new(state: &WidgetState) -> Self {
+        // the children of a scroll view are laid out over the whole content, shifted by the offset
+        let rect = match state.scroll.as_ref() {
+            Some(scroll) => Rect::new(
+                state.rect.x - scroll.offset.x,
+                state.rect.y - scroll.offset.y,
+                state.rect.width.max(scroll.content.width),
+                state.rect.height.max(scroll.content.height),
+            ),
+            None => state.rect,
+        };
+
         Self {
-            rect: state.rect,
+            rect,
             orientation: state.orientation,
             align_h: state.align_h,
             align_v: state.align_v,
+            justify: state.justify,
             padding: state.padding,
             spacing: state.spacing,
+            wrap: state.wrap,
+            line_spacing: state.line_spacing,
+            grid: state.grid.clone(),
         }
     }
 
-    fn offset_x(&self) -> f32 {
-        let pl = self.padding.left;
-        let pr = self.padding.right;
+    /// The rect inside the padding
+    fn content_box(&self) -> Rect {
+        Rect::new(
+            self.rect.x + self.padding.left,
+            self.rect.y + self.padding.top,
+            self.rect.width - self.padding.horizontal(),
+            self.rect.height - self.padding.vertical(),
+        )
+    }
 
-        match self.align_h {
-            AlignH::Left => self.rect.x + pl,
-            AlignH::Center => {
-                self.rect.x + self.rect.width / 2. + pl - pr
-            }
-            AlignH::Right => self.rect.max_x() - pr
+    fn stretches(&self) -> bool {
+        match self.orientation {
+            Orientation::Vertical => self.align_h == AlignH::Stretch,
+            Orientation::Horizontal => self.align_v == AlignV::Stretch,
         }
     }
 
-    fn offset_y(&self) -> f32 {
-        let pt = self.padding.top;
-        let pb = self.padding.bottom;
+    /// The offset from the start of the space & the size of a child on the cross axis.
+    /// The auto margins take the free space first, and a child with one isn't stretched
+    fn align_cross(&self, space: f32, size: f32, margin: AxisMargin) -> (f32, f32) {
+        let free = space - margin.total() - size;
+        let offset = match (margin.start_auto, margin.end_auto) {
+            (true, true) => free.max(0.) / 2.,
+            (true, false) => free.max(0.),
+            (false, true) => 0.,
+            (false, false) => match self.orientation {
+                Orientation::Vertical => match self.align_h {
+                    AlignH::Left | AlignH::Stretch => 0.,
+                    AlignH::Center => free / 2.,
+                    AlignH::Right => free,
+                },
+                Orientation::Horizontal => match self.align_v {
+                    AlignV::Top | AlignV::Stretch => 0.,
+                    AlignV::Middle => free / 2.,
+                    AlignV::Bottom => free,
+                },
+            },
+        };
 
-        match self.align_v {
-            AlignV::Top => self.rect.y + pt,
-            AlignV::Middle => {
-                self.rect.y + self.rect.height / 2. + pt - pb
-            }
-            AlignV::Bottom => self.rect.max_y() - pb,
+        if self.stretches() && !margin.is_auto() {
+            (margin.start, space - margin.total())
+        } else {
+            (margin.start + offset, size)
         }
     }
 
-    fn start_pos(&self, child_total_size: f32, len: f32) -> Vec2f {
-        let offset_x = self.offset_x();
-        let offset_y = self.offset_y();
-        let stretch_factor = self.spacing * (len - 1.);
-        let stretch = child_total_size + stretch_factor;
-
-        match self.orientation {
-            Orientation::Vertical => {
-                let y = match self.align_v {
-                    AlignV::Top => offset_y,
-                    AlignV::Middle => offset_y - stretch / 2.,
-                    AlignV::Bottom => offset_y - stretch,
-                };
-                Vec2f::new(offset_x, y)
-            },
-            Orientation::Horizontal => {
-                let x = match self.align_h {
-                    AlignH::Left => offset_x,
-                    AlignH::Center => offset_x - stretch / 2.,
-                    AlignH::Right => offset_x - stretch,
-                };
-                Vec2f::new(x, offset_y)
-            }
+    /// The space before the first child, the space added between the children,
+    /// and the space taken by each auto margin, which leaves nothing to the justification
+    fn share(&self, free: f32, len: f32, auto_margins: f32) -> (f32, f32, f32) {
+        if auto_margins > 0. && free > 0. {
+            return (0., 0., free / auto_margins);
         }
+        let (lead, extra) = self.justify.distribute(free, len);
+        (lead, extra, 0.)
+    }
+
+    /// The position of the first child on the main axis, the gap between the children,
+    /// and the space taken by each auto margin
+    fn start_pos(&self, child_total_size: f32, len: f32, auto_margins: f32) -> (f32, f32, f32) {
+        let content = self.content_box();
+        let stretch_factor = self.spacing * (len - 1.).max(0.);
+        let (main, start) = match self.orientation {
+            Orientation::Vertical => (content.height, content.y),
+            Orientation::Horizontal => (content.width, content.x),
+        };
+        let free = main - child_total_size - stretch_factor;
+        let (lead, extra, auto) = self.share(free, len, auto_margins);
+
+        (start + lead, self.spacing + extra, auto)
     }
 }
 
@@ -167,7 +363,9 @@ impl LayoutContext {
         });
         Self {
             entity,
-            next_pos: Vec2f::new(0., 0.),
+            next_pos: 0.,
+            gap: 0.,
+            auto_margin: 0.,
             rules,
         }
     }
@@ -179,6 +377,27 @@ impl LayoutContext {
                 .get_all_children(&self.entity)
         });
 
+        if let Some(grid) = self.rules.grid.as_ref() {
+            let children = children.unwrap_or_default();
+            self.arrange_grid(grid, &children);
+            children.iter().for_each(|child| Self::new(*child).calculate());
+            return;
+        }
+
+        if self.rules.wrap
+        && let Some(children) = children.as_ref()
+        {
+            self.resolve_lengths(children);
+            self.rewrap(children);
+            self.arrange_lines(children);
+            children.iter().for_each(|child| Self::new(*child).calculate());
+            return;
+        }
+
+        if let Some(children) = children.as_ref() {
+            self.resolve_flex(children);
+            self.rewrap(children);
+        }
         self.initialize_next_pos(children.as_ref());
 
         if let Some(children) = children {
@@ -190,52 +409,314 @@ impl LayoutContext {
     }
 
     fn initialize_next_pos(&mut self, children: Option<&Vec<ViewId>>) {
-        let (child_total_size, len) = children.map(|c| {(
-            c.iter()
-                .map(|child| {
-                    VIEW_STORAGE.with(|s| {
-                        let tree = s.tree.borrow();
-                        let rect = tree.get(child).unwrap().rect;
-
-                        match self.rules.orientation {
-                            Orientation::Vertical => rect.size().height,
-                            Orientation::Horizontal => rect.size().width,
-                        }
-                    })
+        let content = self.rules.content_box().size();
+        // the size of the children on the main axis with their margins, and the number of auto margins
+        let (child_total_size, auto_margins) = children.map(|c| {
+            VIEW_STORAGE.with(|s| {
+                let tree = s.tree.borrow();
+                c.iter().fold((0., 0.), |(total, autos), child| {
+                    let state = tree.get(child).unwrap();
+                    let (margin, _) = state.margin.axes(self.rules.orientation, Some(content));
+                    let size = match self.rules.orientation {
+                        Orientation::Vertical => state.rect.height,
+                        Orientation::Horizontal => state.rect.width,
+                    };
+                    (total + size + margin.total(), autos + margin.autos())
                 })
-                .sum(),
-            c.len() as f32
-        )})
+            })
+        })
         .unwrap_or_default();
+        let len = children.map(|c| c.len() as f32).unwrap_or_default();
+
+        (self.next_pos, self.gap, self.auto_margin) = self.rules.start_pos(child_total_size, len, auto_margins);
+    }
+
+    /// Resolve the lengths relative to the content box, share the free space on the main axis
+    /// between the children, and stretch them on the cross axis if needed.
+    /// A child with a [`Length::Fill`] on the main axis grows from zero
+    fn resolve_flex(&self, children: &[ViewId]) {
+        let content = self.rules.content_box();
+        let vertical = self.rules.orientation.is_vertical();
+        let (main, cross) = if vertical {
+            (content.height, content.width)
+        } else {
+            (content.width, content.height)
+        };
+        let stretch = self.rules.stretches();
+        // the lengths of a child along the main & the cross axis
+        let axes = |state: &WidgetState| if vertical {
+            (state.height, state.width, state.rect.height)
+        } else {
+            (state.width, state.height, state.rect.width)
+        };
+
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            let bases = children.iter()
+                .map(|child| {
+                    let state = tree.get(child).unwrap();
+                    let (length, _, measured) = axes(state);
+                    match (state.flex_basis, length) {
+                        (Some(basis), _) => basis,
+                        (None, Length::Fill) => 0.,
+                        (None, Length::Percent(percent)) => main * percent / 100.,
+                        (None, _) => measured,
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let margins = children.iter()
+                .map(|child| tree.get(child).unwrap().margin.axes(self.rules.orientation, Some(content.size())))
+                .collect::<Vec<_>>();
+
+            let spacing = self.rules.spacing * children.len().saturating_sub(1) as f32;
+            let margin = margins.iter().map(|(main, _)| main.total()).sum::<f32>();
+            let free = main - spacing - margin - bases.iter().sum::<f32>();
+            let weights = children.iter()
+                .zip(&bases)
+                .map(|(child, basis)| {
+                    let state = tree.get(child).unwrap();
+                    let fill = axes(state).0 == Length::Fill;
+                    match free > 0. {
+                        true if fill => state.flex_grow.max(1.),
+                        true => state.flex_grow,
+                        false => state.flex_shrink * basis,
+                    }
+                })
+                .collect::<Vec<_>>();
+            let total_weight = weights.iter().sum::<f32>();
+
+            children.iter()
+                .zip(bases.iter().zip(&weights).zip(&margins))
+                .for_each(|(child, ((basis, weight), (_, cross_margin)))| {
+                    let state = tree.get_mut(child).unwrap();
+                    let main_size = if total_weight > 0. {
+                        (basis + free * weight / total_weight).max(0.)
+                    } else {
+                        *basis
+                    };
+                    let (_, cross_length, _) = axes(state);
+                    // like the stretch, a fill keeps the margins inside the cross axis
+                    let cross_size = match cross_length {
+                        Length::Fill => Some(cross - cross_margin.total()),
+                        Length::Percent(_) => cross_length.resolve(Some(cross)),
+                        _ => None,
+                    };
+
+                    let mut size = state.rect.size();
+                    let (main_of, cross_of) = if vertical {
+                        (&mut size.height, &mut size.width)
+                    } else {
+                        (&mut size.width, &mut size.height)
+                    };
+                    *main_of = main_size;
+                    if let Some(cross_size) = cross_size {
+                        *cross_of = cross_size;
+                    } else if stretch && !cross_margin.is_auto() {
+                        *cross_of = cross - cross_margin.total();
+                    }
+
+                    let size = state.constrain(size, Some(content.size()));
+                    state.rect.set_size(size);
+                });
+        });
+    }
+
+    /// Resolve the lengths relative to the content box, without sharing the free space
+    fn resolve_lengths(&self, children: &[ViewId]) {
+        let content = self.rules.content_box().size();
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            children.iter().for_each(|child| {
+                let state = tree.get_mut(child).unwrap();
+                let mut size = state.rect.size();
+                if state.width.is_relative() {
+                    size.width = state.width.resolve(Some(content.width)).unwrap_or_default();
+                }
+                if state.height.is_relative() {
+                    size.height = state.height.resolve(Some(content.height)).unwrap_or_default();
+                }
+                let size = state.constrain(size, Some(content));
+                state.rect.set_size(size);
+            });
+        });
+    }
+
+    /// Measure again the cross size of the wrapping children, now that their main size is resolved.
+    /// The measure pass can only break the lines of a stack with a size in pixels on the main axis
+    fn rewrap(&self, children: &[ViewId]) {
+        let content = self.rules.content_box().size();
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            children.iter().for_each(|child| {
+                let state = tree.get(child).unwrap();
+                if !state.wrap || state.grid.is_some() || state.scroll.is_some() { return }
+
+                // a relative cross length is already resolved by this parent
+                let vertical = state.orientation.is_vertical();
+                let cross_length = if vertical { state.width } else { state.height };
+                if cross_length.is_relative() { return }
+
+                // the stretch of this parent is the minimum, if it's on the same axis
+                let (_, cross_margin) = state.margin.axes(self.rules.orientation, Some(content));
+                let stretched = self.rules.stretches()
+                    && !cross_margin.is_auto()
+                    && vertical == self.rules.orientation.is_vertical();
+
+                let (padding, base) = (state.padding, state.base_size());
+                let limit = if vertical {
+                    state.rect.height - padding.vertical()
+                } else {
+                    state.rect.width - padding.horizontal()
+                };
+                let grandchildren = tree.get_all_children(child).unwrap_or_default();
+                let wrapped = stack_content(&tree, child, &grandchildren, Some(limit));
+
+                let state = tree.get_mut(child).unwrap();
+                let mut size = state.rect.size();
+                let (cross, wrapped) = if vertical {
+                    (&mut size.width, base.width.max(wrapped.width + padding.horizontal()))
+                } else {
+                    (&mut size.height, base.height.max(wrapped.height + padding.vertical()))
+                };
+                *cross = if stretched { cross.max(wrapped) } else { wrapped };
+
+                let size = state.constrain(size, Some(content));
+                state.rect.set_size(size);
+                s.update_bounds(child, state);
+            });
+        });
+    }
+
+    /// Break the children into lines along the main axis, stacked on the cross axis.
+    /// Each line is justified on its own, and the children are aligned inside the line
+    fn arrange_lines(&self, children: &[ViewId]) {
+        let content = self.rules.content_box();
+        let vertical = self.rules.orientation.is_vertical();
+        let (main, main_start, cross_start) = if vertical {
+            (content.height, content.y, content.x)
+        } else {
+            (content.width, content.x, content.y)
+        };
+        let spacing = self.rules.spacing;
+
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            let (mains, crosses): (Vec<_>, Vec<_>) = children.iter()
+                .map(|child| {
+                    let size = tree.get(child).unwrap().rect.size();
+                    if vertical { (size.height, size.width) } else { (size.width, size.height) }
+                })
+                .unzip();
+            let margins = children.iter()
+                .map(|child| tree.get(child).unwrap().margin.axes(self.rules.orientation, Some(content.size())))
+                .collect::<Vec<_>>();
+            // the space used by each child with its margins
+            let (outer_mains, outer_crosses): (Vec<_>, Vec<_>) = margins.iter()
+                .enumerate()
+                .map(|(i, (main, cross))| (mains[i] + main.total(), crosses[i] + cross.total()))
+                .unzip();
+
+            let mut line_start = cross_start;
+            break_lines(&outer_mains, main, spacing).into_iter().for_each(|line| {
+                let len = line.len() as f32;
+                let line_cross = outer_crosses[line.clone()].iter().fold(0f32, |a, b| a.max(*b));
+                let free = main - outer_mains[line.clone()].iter().sum::<f32>() - spacing * (len - 1.);
+                let auto_margins = margins[line.clone()].iter().map(|(main, _)| main.autos()).sum();
+                let (lead, extra, auto) = self.rules.share(free, len, auto_margins);
+
+                let mut next = main_start + lead;
+                line.for_each(|i| {
+                    let (main_margin, cross_margin) = margins[i];
+                    let state = tree.get_mut(&children[i]).unwrap();
+                    let (offset, cross_size) = self.rules.align_cross(line_cross, crosses[i], cross_margin);
+                    next += main_margin.before(auto);
+                    let size = if vertical {
+                        Size::new(cross_size, mains[i])
+                    } else {
+                        Size::new(mains[i], cross_size)
+                    };
+                    let pos = if vertical {
+                        Vec2f::new(line_start + offset, next)
+                    } else {
+                        Vec2f::new(next, line_start + offset)
+                    };
+
+                    let size = state.constrain(size, Some(content.size()));
+                    state.rect.set_size(size);
+                    state.rect.set_pos(pos);
+                    s.update_bounds(&children[i], state);
+                    next += mains[i] + main_margin.after(auto) + spacing + extra;
+                });
+
+                line_start += line_cross + self.rules.line_spacing;
+            });
+        });
+    }
+
+    /// A child with a [`Length::Fill`] fills its cell, the [`Length::Percent`] is of the content box of the grid
+    fn arrange_grid(&self, grid: &GridState, children: &[ViewId]) {
+        let content = self.rules.content_box();
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            children.iter().for_each(|child| {
+                let state = tree.get_mut(child).unwrap();
+                if let Length::Percent(_) = state.width {
+                    state.rect.width = state.width.resolve(Some(content.width)).unwrap_or_default();
+                }
+                if let Length::Percent(_) = state.height {
+                    state.rect.height = state.height.resolve(Some(content.height)).unwrap_or_default();
+                }
+            });
+            let items = grid_items(&tree, grid, children, Some(content.size()));
+            let rects = grid.arrange(
+                &items,
+                content,
+                self.rules.align_h,
+                self.rules.align_v,
+            );
 
-        self.next_pos = self.rules.start_pos(child_total_size, len);
+            children.iter().zip(rects).for_each(|(child, rect)| {
+                let state = tree.get_mut(child).unwrap();
+                let margin = state.margin.resolve(Some(content.size()));
+                state.rect.set_pos(rect.vec2f() + Vec2f::new(margin.left, margin.top));
+                let size = Size::new(
+                    rect.width - margin.horizontal(),
+                    rect.height - margin.vertical(),
+                );
+                let size = state.constrain(size, Some(content.size()));
+                state.rect.set_size(size);
+                s.update_bounds(child, state);
+            });
+        });
     }
 
     // FIXME: this is not possible without signal?
     fn assign_position(&mut self, child: &ViewId) {
+        let content = self.rules.content_box();
         VIEW_STORAGE.with(|s| {
             let mut tree = s.tree.borrow_mut();
             let state = tree.get_mut(child).unwrap();
             let size = state.rect.size();
+            let (main_margin, cross_margin) = state.margin.axes(self.rules.orientation, Some(content.size()));
+            let before = main_margin.before(self.auto_margin);
+            let after = main_margin.after(self.auto_margin);
 
             match self.rules.orientation {
                 Orientation::Vertical => {
-                    match self.rules.align_h {
-                        AlignH::Left | AlignH::Right => state.rect.x = self.next_pos.x,
-                        AlignH::Center => state.rect.x = self.next_pos.x - size.width / 2.,
-                    }
-                    state.rect.y = self.next_pos.y;
-                    self.next_pos.y += self.rules.spacing + size.height;
+                    let (offset, _) = self.rules.align_cross(content.width, size.width, cross_margin);
+                    state.rect.x = content.x + offset;
+                    state.rect.y = self.next_pos + before;
+                    self.next_pos += before + size.height + after + self.gap;
                 },
                 Orientation::Horizontal => {
-                    state.rect.x = self.next_pos.x;
-                    match self.rules.align_v {
-                        AlignV::Top | AlignV::Bottom => state.rect.y = self.next_pos.y,
-                        AlignV::Middle => state.rect.y = self.next_pos.y - size.height / 2.,
-                    }
-                    self.next_pos.x += self.rules.spacing + size.width;
+                    let (offset, _) = self.rules.align_cross(content.height, size.height, cross_margin);
+                    state.rect.x = self.next_pos + before;
+                    state.rect.y = content.y + offset;
+                    self.next_pos += before + size.width + after + self.gap;
                 },
             }
+            s.update_bounds(child, state);
         });
     }
 }
@@ -246,35 +727,70 @@ pub(crate) fn calculate_size_recursive(id: &ViewId) -> Size {
         let state = tree.get(id).unwrap();
         let padding = state.padding;
         let orientation = state.orientation;
-        let spacing = state.spacing;
-        let mut size = state.rect.size();
+        let is_scroll = state.scroll.is_some();
+        let grid = state.grid.clone();
+        let main_length = match orientation {
+            Orientation::Vertical => state.height,
+            Orientation::Horizontal => state.width,
+        };
+        let wrap_limit = match orientation {
+            Orientation::Vertical => main_length.resolve(None).map(|h| h - padding.vertical()),
+            Orientation::Horizontal => main_length.resolve(None).map(|w| w - padding.horizontal()),
+        }
+        .filter(|_| state.wrap);
+        let sized_by_parent = state.wrap && main_length.is_relative();
+        let mut size = match state.text.as_ref() {
+            Some(text) if text.fit_content => {
+                let text_size = text.measure();
+                Size::new(
+                    text_size.width + padding.horizontal(),
+                    text_size.height + padding.vertical(),
+                )
+            },
+            // a scroll view keeps its own size, and only measures the content
+            _ if is_scroll => Size::default(),
+            _ => state.base_size(),
+        };
         let maybe_children = tree.get_all_children(id);
         drop(tree);
 
-        if let Some(children) = maybe_children {
-            children.iter().for_each(|child_id| {
-                let child_size = calculate_size_recursive(child_id);
+        if let Some(grid) = grid.as_ref() {
+            let children = maybe_children.unwrap_or_default();
+            children.iter().for_each(|child_id| { calculate_size_recursive(child_id); });
+            let content = grid.measure(&grid_items(&s.tree.borrow(), grid, &children, None));
+            size.width = size.width.max(content.width + padding.horizontal());
+            size.height = size.height.max(content.height + padding.vertical());
+        } else if let Some(children) = maybe_children {
+            children.iter().for_each(|child_id| { calculate_size_recursive(child_id); });
+            let tree = s.tree.borrow();
+            let mut content = stack_content(&tree, id, &children, wrap_limit);
+
+            // a wrapping stack sized by its parent only asks for its widest child on the main axis,
+            // and its lines are measured again once its size is resolved
+            if sized_by_parent {
+                let widest = stack_content(&tree, id, &children, Some(0.));
                 match orientation {
-                    Orientation::Vertical => {
-                        size.height += child_size.height;
-                        size.width = size.width.max(child_size.width + padding.horizontal());
-                    }
-                    Orientation::Horizontal => {
-                        size.height = size.height.max(child_size.height + padding.vertical());
-                        size.width += child_size.width;
-                    }
+                    Orientation::Vertical => content.height = widest.height,
+                    Orientation::Horizontal => content.width = widest.width,
                 }
-            });
-            let child_len = children.len() as f32;
-            let stretch = spacing * (child_len - 1.);
-            match orientation {
-                Orientation::Vertical => {
-                    size.height += padding.vertical() + stretch;
-                },
-                Orientation::Horizontal => {
-                    size.width += padding.horizontal() + stretch;
-                },
             }
+            drop(tree);
+
+            // the set size is the minimum, so measuring again gives the same size
+            size.width = size.width.max(content.width + padding.horizontal());
+            size.height = size.height.max(content.height + padding.vertical());
+        }
+
+        if is_scroll {
+            let mut tree_mut = s.tree.borrow_mut();
+            let state_mut = tree_mut.get_mut(id).unwrap();
+            let viewport = state_mut.base_size();
+            state_mut.rect.set_size(viewport);
+            if let Some(scroll) = state_mut.scroll.as_mut() {
+                scroll.content = size;
+                scroll.offset = scroll.clamp(scroll.offset, viewport);
+            }
+            return viewport;
         }
 
         let tree = s.tree.borrow();
@@ -291,16 +807,372 @@ pub(crate) fn calculate_size_recursive(id: &ViewId) -> Size {
             }
         }
 
-        let final_size = size
-            .adjust_on_min_constraints(state.min_width, state.min_height)
-            .adjust_on_max_constraints(state.max_width, state.max_height);
+        let final_size = state.constrain(size, None);
 
         drop(tree);
 
         let mut tree_mut = s.tree.borrow_mut();
         let state_mut = tree_mut.get_mut(id).unwrap();
         state_mut.rect.set_size(final_size);
+        s.update_bounds(id, state_mut);
 
         final_size
     })
 }
+
+/// Measure & lay out again around the views whose size or children have changed.
+/// Their ancestors are measured again as long as their size changes,
+/// then laid out from the first one which kept its size, or from the root of the layer
+pub(crate) fn relayout(changed: &[ViewId]) {
+    let mut tops = Vec::<ViewId>::new();
+
+    changed.iter().for_each(|id| {
+        if !VIEW_STORAGE.with(|s| s.tree.borrow().contains(id)) { return }
+
+        let mut top = *id;
+        calculate_size_recursive(id);
+        while let Some((parent, size)) = VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            tree.get_parent(&top).map(|parent| (*parent, tree.get(parent).unwrap().rect.size()))
+        }) {
+            top = parent;
+            if calculate_size_recursive(&parent) == size { break }
+        }
+
+        if !tops.contains(&top) {
+            tops.push(top);
+        }
+    });
+
+    tops.iter().for_each(|top| LayoutContext::new(*top).calculate());
+}
+
+/// The size used by the measured children of a stack with their margins & the spacing.
+/// They are broken into lines if the stack wraps within a limit on the main axis
+fn stack_content(
+    tree: &aplite_storage::Tree<ViewId, WidgetState>,
+    id: &ViewId,
+    children: &[ViewId],
+    wrap_limit: Option<f32>,
+) -> Size {
+    let state = tree.get(id).unwrap();
+    let (orientation, spacing, line_spacing) = (state.orientation, state.spacing, state.line_spacing);
+
+    // the space used by each child along the main & the cross axis, with its margins
+    let (mains, crosses): (Vec<_>, Vec<_>) = children.iter()
+        .map(|child_id| {
+            let child = tree.get(child_id).unwrap();
+            let child_size = child.rect.size();
+            let (basis, margin) = (child.flex_basis, child.margin.resolve(None));
+            match orientation {
+                Orientation::Vertical => (
+                    basis.unwrap_or(child_size.height) + margin.vertical(),
+                    child_size.width + margin.horizontal(),
+                ),
+                Orientation::Horizontal => (
+                    basis.unwrap_or(child_size.width) + margin.horizontal(),
+                    child_size.height + margin.vertical(),
+                ),
+            }
+        })
+        .unzip();
+
+    // the lines only break if the stack has a size on the main axis
+    let lines = break_lines(&mains, wrap_limit.unwrap_or(f32::INFINITY), spacing);
+    let (main, cross) = lines.iter().fold((0f32, 0f32), |(main, cross), line| {
+        let stretch = spacing * line.len().saturating_sub(1) as f32;
+        let line_main = mains[line.clone()].iter().sum::<f32>() + stretch;
+        let line_cross = crosses[line.clone()].iter().fold(0f32, |a, b| a.max(*b));
+        (main.max(line_main), cross + line_cross)
+    });
+    let cross = cross + line_spacing * lines.len().saturating_sub(1) as f32;
+
+    match orientation {
+        Orientation::Vertical => Size::new(cross, main),
+        Orientation::Horizontal => Size::new(main, cross),
+    }
+}
+
+/// Break the children into lines by their index, so each line fits in the limit.
+/// A line always has at least one child, even if it's larger than the limit
+fn break_lines(sizes: &[f32], limit: f32, spacing: f32) -> Vec<Range<usize>> {
+    let mut lines = vec![];
+    let mut start = 0;
+    let mut used = 0.;
+
+    sizes.iter().enumerate().for_each(|(i, size)| {
+        if i == start {
+            used = *size;
+        } else if used + spacing + size > limit {
+            lines.push(start..i);
+            start = i;
+            used = *size;
+        } else {
+            used += spacing + size;
+        }
+    });
+    if start < sizes.len() {
+        lines.push(start..sizes.len());
+    }
+
+    lines
+}
+
+/// The resolved cell & the size of each child of the grid with its margins,
+/// given the content box of the grid if known
+fn grid_items(
+    tree: &aplite_storage::Tree<ViewId, WidgetState>,
+    grid: &GridState,
+    children: &[ViewId],
+    content: Option<Size>,
+) -> Vec<(GridCell, Size)> {
+    let cells = children.iter()
+        .map(|child| tree.get(child).unwrap().grid_cell)
+        .collect::<Vec<_>>();
+
+    grid.place(&cells)
+        .into_iter()
+        .zip(children)
+        .map(|(mut cell, child)| {
+            let state = tree.get(child).unwrap();
+            if state.width == Length::Fill { cell.align_h = Some(AlignH::Stretch) }
+            if state.height == Length::Fill { cell.align_v = Some(AlignV::Stretch) }
+            cell
+        })
+        .zip(children.iter().map(|child| {
+            let state = tree.get(child).unwrap();
+            let margin = state.margin.resolve(content);
+            Size::new(
+                state.rect.width + margin.horizontal(),
+                state.rect.height + margin.vertical(),
+            )
+        }))
+        .collect()
+}
+
+#[cfg(test)]
+mod layout_test {
+    use super::*;
+    use crate::widget::{Button, HStack, VStack, Widget, WidgetExt};
+
+    fn rect_of(id: ViewId) -> Rect {
+        VIEW_STORAGE.with(|s| s.tree.borrow().get(&id).unwrap().rect)
+    }
+
+    #[test]
+    fn flex_shares_the_main_axis() {
+        let fixed = Button::new().size((50, 20));
+        let grow_one = Button::new().size((50, 30)).flex_grow(1.);
+        let grow_two = Button::new().size((10, 10)).flex_basis(40.).flex_grow(2.);
+        let ids = [fixed.id(), grow_one.id(), grow_two.id()];
+
+        let row = HStack::new()
+            .size((310, 40))
+            .set_state(|state| {
+                state.set_spacing(10.);
+                state.set_align_v(AlignV::Stretch);
+            })
+            .child(fixed)
+            .child(grow_one)
+            .child(grow_two);
+        LayoutContext::new(row.id()).calculate();
+
+        // the 150px left are shared 1:2, and the cross axis fills the height
+        assert_eq!(rect_of(ids[0]), Rect::new(0., 0., 50., 40.));
+        assert_eq!(rect_of(ids[1]), Rect::new(60., 0., 100., 40.));
+        assert_eq!(rect_of(ids[2]), Rect::new(170., 0., 140., 40.));
+
+        let shrink = Button::new().size((20, 60)).flex_shrink(1.);
+        let shrink_more = Button::new().size((20, 30)).flex_shrink(2.);
+        let ids = [shrink.id(), shrink_more.id()];
+
+        let column = VStack::new()
+            .size((20, 60))
+            .child(shrink)
+            .child(shrink_more);
+        LayoutContext::new(column.id()).calculate();
+
+        // the 30px overflow is weighted by the factor & the basis
+        assert_eq!(rect_of(ids[0]).height, 45.);
+        assert_eq!(rect_of(ids[1]).height, 15.);
+    }
+
+    #[test]
+    fn lengths_relative_to_the_parent() {
+        let half = Button::new().width(Length::Percent(50.)).height(Length::Fill);
+        let fill = Button::new().width(Length::Fill).set_state(|state| {
+            state.set_height(Length::Percent(25.));
+            state.set_max_width(Length::Percent(25.));
+        });
+        let fixed = Button::new().size((20, 20));
+        let ids = [half.id(), fill.id(), fixed.id()];
+
+        let row = HStack::new()
+            .size((200, 100))
+            .set_state(|state| state.set_padding(Padding::splat(10.)))
+            .child(half)
+            .child(fill)
+            .child(fixed);
+        let row_id = row.id();
+
+        // the relative children don't grow the parent
+        assert_eq!(calculate_size_recursive(&row_id), Size::new(200., 100.));
+        LayoutContext::new(row_id).calculate();
+
+        // the content box is 180x80, and the fill is capped at 25% of the width
+        assert_eq!(rect_of(ids[0]), Rect::new(10., 10., 90., 80.));
+        assert_eq!(rect_of(ids[1]), Rect::new(100., 10., 45., 20.));
+        assert_eq!(rect_of(ids[2]), Rect::new(145., 10., 20., 20.));
+
+        // the same size when measured again
+        calculate_size_recursive(&row_id);
+        LayoutContext::new(row_id).calculate();
+        assert_eq!(rect_of(ids[1]), Rect::new(100., 10., 45., 20.));
+    }
+
+    #[test]
+    fn justify_spreads_the_free_space() {
+        let children = [(); 3].map(|_| Button::new().size((20, 20)));
+        let ids = children.each_ref().map(Widget::id);
+        let row = children
+            .into_iter()
+            .fold(HStack::new().size((240, 40)), |row, child| row.child(child))
+            .set_state(|state| state.set_align_v(AlignV::Bottom));
+        let row_id = row.id();
+
+        let cases = [
+            (Justify::Start, [0., 20., 40.]),
+            (Justify::End, [180., 200., 220.]),
+            (Justify::SpaceBetween, [0., 110., 220.]),
+            (Justify::SpaceAround, [30., 110., 190.]),
+            (Justify::SpaceEvenly, [45., 110., 175.]),
+        ];
+
+        cases.into_iter().for_each(|(justify, expected)| {
+            VIEW_STORAGE.with(|s| s.tree.borrow_mut().get_mut(&row_id).unwrap().set_justify(justify));
+            LayoutContext::new(row_id).calculate();
+
+            assert_eq!(ids.map(|id| rect_of(id).x), expected, "{justify:?}");
+            // the alignment only moves the children on the cross axis
+            assert!(ids.iter().all(|id| rect_of(*id).y == 20.));
+        });
+    }
+
+    #[test]
+    fn wrap_follows_a_relative_width() {
+        let chips = [(); 3].map(|_| Button::new().size((40, 20)));
+        let ids = chips.each_ref().map(Widget::id);
+        let row = chips
+            .into_iter()
+            .fold(HStack::new().width(Length::Percent(100.)), |row, chip| row.child(chip))
+            .set_state(|state| state.set_wrap(true));
+        let row_id = row.id();
+        let below = Button::new().size((40, 20));
+        let below_id = below.id();
+        let column = VStack::new().size((100, 200)).child(row).child(below);
+        let column_id = column.id();
+
+        // without a width in pixels, the row is measured on a single line
+        calculate_size_recursive(&column_id);
+        assert_eq!(rect_of(row_id).height, 20.);
+
+        // the row breaks at the width of the column, and pushes its sibling down
+        LayoutContext::new(column_id).calculate();
+        assert_eq!(rect_of(row_id).size(), Size::new(100., 40.));
+        assert_eq!(ids.map(|id| rect_of(id).vec2f()), [
+            Vec2f::new(0., 0.),
+            Vec2f::new(40., 0.),
+            Vec2f::new(0., 20.),
+        ]);
+        assert_eq!(rect_of(below_id).y, 40.);
+    }
+
+    #[test]
+    fn margins_take_space_around_the_children() {
+        let top = Button::new().size((20, 20)).margin(Margin::new(10., 5., 4., 0.));
+        let centered = Button::new().size((30, 20)).margin(Margin::auto());
+        let bottom = Button::new().size((20, 10)).margin(Margin::splat(2.));
+        let ids = [top.id(), centered.id(), bottom.id()];
+        let column = VStack::new().child(top).child(centered).child(bottom);
+        let column_id = column.id();
+
+        // the auto margins are zero when measuring
+        assert_eq!(calculate_size_recursive(&column_id), Size::new(30., 69.));
+
+        VIEW_STORAGE.with(|s| s.tree.borrow_mut().get_mut(&column_id).unwrap().rect.set_size(Size::new(100., 129.)));
+        LayoutContext::new(column_id).calculate();
+
+        // the auto margins share the free 60px on the main axis, and center on the cross axis
+        assert_eq!(ids.map(|id| rect_of(id).vec2f()), [
+            Vec2f::new(4., 10.),
+            Vec2f::new(35., 65.),
+            Vec2f::new(2., 117.),
+        ]);
+    }
+
+    #[test]
+    fn fill_keeps_the_margins_on_the_cross_axis() {
+        let child = Button::new().height(20).width(Length::Fill).margin(Margin::new(0., 0., 10., 5.));
+        let child_id = child.id();
+        let column = VStack::new()
+            .size((100, 100))
+            .set_state(|state| state.set_align_h(AlignH::Left))
+            .child(child);
+        let column_id = column.id();
+
+        calculate_size_recursive(&column_id);
+        LayoutContext::new(column_id).calculate();
+
+        assert_eq!(rect_of(child_id), Rect::new(10., 0., 85., 20.));
+    }
+
+    #[test]
+    fn relative_margins_dont_grow_the_parent() {
+        let child = Button::new().size((40, 20)).margin(Margin {
+            left: Length::Percent(10.),
+            ..Margin::splat(0.)
+        });
+        let child_id = child.id();
+        let column = VStack::new().child(child);
+        let column_id = column.id();
+
+        // measured without the relative margin
+        assert_eq!(calculate_size_recursive(&column_id), Size::new(40., 20.));
+
+        // and resolved against the content box of the parent once it's known
+        VIEW_STORAGE.with(|s| s.tree.borrow_mut().get_mut(&column_id).unwrap().rect.set_size(Size::new(200., 20.)));
+        LayoutContext::new(column_id).calculate();
+        assert_eq!(rect_of(child_id).vec2f(), Vec2f::new(20., 0.));
+    }
+
+    #[test]
+    fn wrap_breaks_into_lines() {
+        let sizes = [(40, 20), (40, 10), (30, 30), (60, 10), (30, 20)];
+        let children = sizes.map(|size| Button::new().size(size));
+        let ids = children.each_ref().map(Widget::id);
+        let row = children
+            .into_iter()
+            .fold(HStack::new().width(100), |row, child| row.child(child))
+            .set_state(|state| {
+                state.set_wrap(true);
+                state.set_spacing(10.);
+                state.set_line_spacing(5.);
+                state.set_justify(Justify::Center);
+                state.set_align_v(AlignV::Middle);
+            });
+        let row_id = row.id();
+
+        // three lines: 90px, 100px & 30px wide
+        assert_eq!(calculate_size_recursive(&row_id), Size::new(100., 80.));
+        LayoutContext::new(row_id).calculate();
+
+        let positions = ids.map(|id| rect_of(id).vec2f());
+        assert_eq!(positions, [
+            Vec2f::new(5., 0.),
+            Vec2f::new(55., 5.),
+            Vec2f::new(0., 25.),
+            Vec2f::new(40., 35.),
+            Vec2f::new(35., 60.),
+        ]);
+    }
+}
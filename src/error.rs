@@ -1,4 +1,4 @@
-use aplite_renderer::{RenderError, InitiationError};
+use aplite_renderer::{RenderError, InitiationError, FontError};
 
 #[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
@@ -7,6 +7,7 @@ pub enum ApliteError {
     WindowCreationFailed(winit::error::OsError),
     RenderError(RenderError),
     InitiationError(InitiationError),
+    FontError(FontError),
 }
 
 impl std::fmt::Display for ApliteError {
@@ -16,6 +17,7 @@ impl std::fmt::Display for ApliteError {
             Self::WindowCreationFailed(err) => write!(f, "{err:?}"),
             Self::RenderError(err) => write!(f, "{err:?}"),
             Self::InitiationError(err) => write!(f, "{err:?}"),
+            Self::FontError(err) => write!(f, "{err:?}"),
         }
     }
 }
@@ -45,3 +47,9 @@ impl From<InitiationError> for ApliteError {
         Self::InitiationError(value)
     }
 }
+
+impl From<FontError> for ApliteError {
+    fn from(value: FontError) -> Self {
+        Self::FontError(value)
+    }
+}
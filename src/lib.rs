@@ -9,17 +9,22 @@ pub mod prelude {
     use crate::error::ApliteError;
 
     pub use aplite_reactive::*;
-    pub use aplite_renderer::Shape;
-    pub use aplite_types::{Rgba, rgba_u8, rgba_f32, rgba_hex, CornerRadius};
+    pub use aplite_renderer::{Shape, Font};
+    pub use aplite_types::{Rgba, rgba_u8, rgba_f32, rgba_hex, CornerRadius, Length, Vec2f};
 
     pub use crate::app::Aplite;
     pub use crate::context::Context;
+    pub use crate::context::cursor::{ClickSettings, CursorIcon, MouseButton, MouseEvent};
+    pub use crate::context::keyboard::KeyEvent;
+    pub use winit::keyboard::{Key, NamedKey, ModifiersState};
     pub use crate::state::AspectRatio;
     pub use crate::context::layout::{
         Orientation,
         Padding,
+        Margin,
         AlignV,
-        AlignH
+        AlignH,
+        Justify,
     };
     pub use crate::widget::{
         Widget,
@@ -29,11 +34,30 @@ pub mod prelude {
         VStack,
         Button,
         Image,
+        Text,
+        TextInput,
+        ScrollView,
+        Grid,
+        GridCell,
+        Track,
+        For,
+        Show,
+        Switch,
+        Popup,
+        Placement,
         h_stack,
         v_stack,
         button,
         image,
         image_reader,
+        text,
+        text_input,
+        scroll_view,
+        grid,
+        for_each,
+        show,
+        switch,
+        popup,
     };
     pub use crate::view::{
         IntoView,
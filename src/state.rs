@@ -1,17 +1,19 @@
-use aplite_renderer::Shape;
+use aplite_renderer::{Font, Shape};
 use aplite_types::{
     Matrix3x2,
     Rect,
     Size,
     Vec2f,
     CornerRadius,
+    Length,
     Paint,
+    PaintRef,
     Rgba,
 };
 
-use crate::context::layout::{AlignV, AlignH, Orientation, Padding};
-use crate::context::cursor::Cursor;
-use crate::widget::WidgetEvent;
+use crate::context::layout::{AlignV, AlignH, Justify, Margin, Orientation, Padding};
+use crate::context::cursor::{Cursor, CursorIcon};
+use crate::widget::{WidgetEvent, TextState, ScrollState, GridState, GridCell};
 
 #[derive(Debug, Clone, Copy)]
 pub enum AspectRatio {
@@ -24,18 +26,35 @@ pub enum AspectRatio {
 pub struct WidgetState {
     pub(crate) name: &'static str,
     pub(crate) rect: Rect,
+    /// The size set on the widget, the layout measures & resolves the rect from it
+    pub(crate) width: Length,
+    pub(crate) height: Length,
     pub(crate) rotation: f32, // in radians
     // pub(crate) transform: Matrix3x2,
-    pub(crate) min_width: Option<f32>,
-    pub(crate) min_height: Option<f32>,
-    pub(crate) max_width: Option<f32>,
-    pub(crate) max_height: Option<f32>,
+    pub(crate) min_width: Option<Length>,
+    pub(crate) min_height: Option<Length>,
+    pub(crate) max_width: Option<Length>,
+    pub(crate) max_height: Option<Length>,
     pub(crate) align_v: AlignV,
     pub(crate) align_h: AlignH,
+    pub(crate) justify: Justify,
     pub(crate) orientation: Orientation,
     pub(crate) padding: Padding,
+    /// The space kept around it inside the parent
+    pub(crate) margin: Margin,
     pub(crate) spacing: f32,
-    // pub(crate) z_index: u32,
+    /// Break the children of a stack into lines, when they don't fit on the main axis
+    pub(crate) wrap: bool,
+    /// The space between the lines of a wrapping stack
+    pub(crate) line_spacing: f32,
+    /// The share of the free space on the main axis of the parent stack it grows into
+    pub(crate) flex_grow: f32,
+    /// The share of the overflow on the main axis of the parent stack it gives back, weighted by the basis
+    pub(crate) flex_shrink: f32,
+    /// The size on the main axis of the parent stack before growing or shrinking, the measured size if [`None`]
+    pub(crate) flex_basis: Option<f32>,
+    /// The stacking order among the siblings, the higher ones are drawn above
+    pub(crate) z_index: i32,
     pub(crate) image_aspect_ratio: AspectRatio,
     pub(crate) shape: Shape,
     pub(crate) corner_radius: CornerRadius,
@@ -43,8 +62,20 @@ pub struct WidgetState {
     pub(crate) event: Option<WidgetEvent>,
     pub(crate) background: Paint,
     pub(crate) border_color: Paint,
+    pub(crate) hover_color: Option<Rgba<u8>>,
+    pub(crate) click_color: Option<Rgba<u8>>,
+    pub(crate) text: Option<TextState>,
+    pub(crate) scroll: Option<ScrollState>,
+    pub(crate) grid: Option<GridState>,
+    /// the placement inside the parent, if it's a [`Grid`](crate::widget::Grid)
+    pub(crate) grid_cell: Option<GridCell>,
     pub(crate) dragable: bool,
     pub(crate) hoverable: bool,
+    pub(crate) focusable: bool,
+    /// [`None`] uses the icon of the closest ancestor which has one
+    pub(crate) cursor_icon: Option<CursorIcon>,
+    /// clip the children to the shape
+    pub(crate) clip: bool,
 }
 
 impl std::fmt::Debug for WidgetState {
@@ -58,26 +89,44 @@ impl Default for WidgetState {
         Self {
             name: "",
             rect: Rect::new(0.0, 0.0, 1.0, 1.0),
+            width: Length::Auto,
+            height: Length::Auto,
             rotation: 0.0,
             // transform: Matrix3x2::identity(),
-            min_width: Some(1.),
-            min_height: Some(1.),
+            min_width: Some(Length::Px(1.)),
+            min_height: Some(Length::Px(1.)),
             max_width: None,
             max_height: None,
             align_v: AlignV::Top,
             align_h: AlignH::Left,
+            justify: Justify::Start,
             orientation: Orientation::Vertical,
             spacing: 0.,
-            // z_index: 0,
+            wrap: false,
+            line_spacing: 0.,
+            flex_grow: 0.,
+            flex_shrink: 0.,
+            flex_basis: None,
+            z_index: 0,
             padding: Padding::default(),
+            margin: Margin::default(),
             image_aspect_ratio: AspectRatio::Undefined,
             dragable: false,
             hoverable: false,
+            focusable: false,
+            cursor_icon: None,
+            clip: false,
             shape: Shape::Rect,
             corner_radius: CornerRadius::splat(0.0),
             event: None,
             background: Paint::Color(Rgba::RED),
             border_color: Paint::Color(Rgba::WHITE),
+            hover_color: None,
+            click_color: None,
+            text: None,
+            scroll: None,
+            grid: None,
+            grid_cell: None,
             border_width: 0.0,
         }
     }
@@ -89,6 +138,8 @@ impl WidgetState {
         Self {
             name: "Root",
             rect: Rect::from_size(size),
+            width: Length::Px(size.width),
+            height: Length::Px(size.height),
             align_h: AlignH::Center,
             background: Paint::Color(Rgba::TRANSPARENT),
             border_color: Paint::Color(Rgba::TRANSPARENT),
@@ -96,22 +147,191 @@ impl WidgetState {
         }
     }
 
-    // FIXME: consider rotation & maybe some precision
+    /// Shape-exact hit test, using the same distance functions as the shader.
+    /// The position is mapped into the unrotated space of the widget first
     pub(crate) fn detect_hover(&self, cursor: &Cursor) -> bool {
-        self.rect.contains(cursor.hover.pos)
+        self.hit_test(cursor.hover.pos)
+    }
+
+    pub(crate) fn hit_test(&self, pos: Vec2f) -> bool {
+        let Rect { width, height, .. } = self.rect;
+        if width <= 0.0 || height <= 0.0 { return false }
+
+        let p = self.to_local(pos);
+        // the border is antialiased on both sides of the edge
+        let border = self.drawn_border_width() / 2.0;
+        let half = Vec2f::new(width, height) / 2.0 - Vec2f::splat(border);
+
+        let distance = match self.shape {
+            Shape::Circle => sd_circle(p, half.x.min(half.y)),
+            Shape::Rect => sd_rounded_rect(p, half, CornerRadius::splat(0.0)),
+            Shape::RoundedRect => sd_rounded_rect(p, half, self.clip_corners()),
+            // drawn as a plain quad
+            Shape::Triangle => return p.x.abs() <= width / 2.0 && p.y.abs() <= height / 2.0,
+        };
+
+        distance < border
+    }
+
+    /// The axis aligned box around the rotated shape, including the outer half of the border
+    pub(crate) fn hit_bounds(&self) -> Rect {
+        let (sin, cos) = self.rotation.sin_cos();
+        let border = self.drawn_border_width() / 2.0;
+        let half_w = (self.rect.width * cos.abs() + self.rect.height * sin.abs()) / 2.0 + border;
+        let half_h = (self.rect.width * sin.abs() + self.rect.height * cos.abs()) / 2.0 + border;
+
+        Rect::new(
+            self.rect.center_x() - half_w,
+            self.rect.center_y() - half_h,
+            half_w * 2.0,
+            half_h * 2.0,
+        )
+    }
+
+    /// The position relative to the center of the rect, with the rotation undone
+    fn to_local(&self, pos: Vec2f) -> Vec2f {
+        let d = pos - Vec2f::new(self.rect.center_x(), self.rect.center_y());
+        if self.rotation == 0.0 { return d }
+
+        let (sin, cos) = self.rotation.sin_cos();
+        Vec2f::new(cos * d.x + sin * d.y, cos * d.y - sin * d.x)
+    }
+
+    /// The border width sent to the shader, which falls back to 5 pixels when unset
+    pub(crate) fn drawn_border_width(&self) -> f32 {
+        if self.border_width == 0.0 { 5.0 } else { self.border_width }
+    }
+
+    /// The set size before fitting the content, where the lengths depending on the parent or the content are zero
+    pub(crate) fn base_size(&self) -> Size {
+        Size::new(
+            self.width.resolve(None).unwrap_or_default(),
+            self.height.resolve(None).unwrap_or_default(),
+        )
+    }
+
+    /// The size clamped to the min & max constraints.
+    /// The constraints relative to the parent are skipped if its content box isn't known
+    pub(crate) fn constrain(&self, size: Size, parent: Option<Size>) -> Size {
+        let (width, height) = (parent.map(|p| p.width), parent.map(|p| p.height));
+        let resolve = |length: Option<Length>, parent| length.and_then(|length| length.resolve(parent));
+
+        size.adjust_on_min_constraints(resolve(self.min_width, width), resolve(self.min_height, height))
+            .adjust_on_max_constraints(resolve(self.max_width, width), resolve(self.max_height, height))
+    }
+
+    /// The background to be rendered, taking the hover & click colors into account
+    pub(crate) fn background_paint(&self, is_hovered: bool, is_clicked: bool) -> PaintRef<'_> {
+        let color = if is_clicked {
+            self.click_color.as_ref().or(self.hover_color.as_ref())
+        } else if is_hovered {
+            self.hover_color.as_ref()
+        } else {
+            None
+        };
+
+        color
+            .map(PaintRef::Color)
+            .unwrap_or_else(|| self.background.as_paint_ref())
+    }
+
+    /// A [`ScrollView`](crate::widget::ScrollView) always clips its content
+    pub(crate) fn clips_children(&self) -> bool {
+        self.clip || self.scroll.is_some()
+    }
+
+    /// The corner radii of the shape in logical pixels.
+    /// The [`CornerRadius`] is scaled by the width, the same way the shader does
+    pub(crate) fn clip_corners(&self) -> CornerRadius {
+        match self.shape {
+            Shape::Circle => CornerRadius::splat(self.rect.width.min(self.rect.height) / 2.0),
+            Shape::RoundedRect => {
+                let r = self.corner_radius;
+                let scale = self.rect.width / 400.0;
+                CornerRadius::new(r.tl * scale, r.bl * scale, r.br * scale, r.tr * scale)
+            },
+            Shape::Rect | Shape::Triangle => CornerRadius::splat(0.0),
+        }
     }
 
     pub(crate) fn get_transform(&self, screen: Size) -> Matrix3x2 {
-        let rect = self.rect;
-        let tx = rect.center_x() / screen.width * 2.0 - 1.0;
-        let ty = 1.0 - rect.center_y() / screen.height * 2.0;
-        let sx = rect.width / screen.width;
-        let sy = rect.height / screen.height;
+        get_transform(self.shape_rect(), self.rotation, screen)
+    }
+
+    /// The rect the shape is drawn & clipped in.
+    /// A circle fits in the largest square centered in the rect, since the shader fills the whole quad
+    pub(crate) fn shape_rect(&self) -> Rect {
+        match self.shape {
+            Shape::Circle => {
+                let side = self.rect.width.min(self.rect.height);
+                Rect::new(
+                    self.rect.center_x() - side / 2.0,
+                    self.rect.center_y() - side / 2.0,
+                    side,
+                    side,
+                )
+            },
+            _ => self.rect,
+        }
+    }
+
+    /// Resize the rect to wrap the text content, or scroll the content to keep the caret visible
+    pub(crate) fn fit_text(&mut self) {
+        let visible = self.rect.width - self.padding.horizontal();
+        let Some(text) = self.text.as_mut() else { return };
+
+        if text.fit_content {
+            let size = text.measure();
+            self.rect.set_size(Size::new(
+                size.width + self.padding.horizontal(),
+                size.height + self.padding.vertical(),
+            ));
+        } else {
+            text.scroll_to_caret(visible);
+        }
+    }
 
-        Matrix3x2::from_scale_rad_translate(sx, sy, self.rotation, tx, ty)
+    /// The content box of a text which doesn't fit its content, where the glyphs are clipped
+    pub(crate) fn text_clip(&self) -> Option<Rect> {
+        self.text
+            .as_ref()
+            .filter(|text| !text.fit_content)
+            .map(|_| Rect::new(
+                self.rect.x + self.padding.left,
+                self.rect.y + self.padding.top,
+                self.rect.width - self.padding.horizontal(),
+                self.rect.height - self.padding.vertical(),
+            ))
     }
 }
 
+pub(crate) fn get_transform(rect: Rect, rotation: f32, screen: Size) -> Matrix3x2 {
+    let tx = rect.center_x() / screen.width * 2.0 - 1.0;
+    let ty = 1.0 - rect.center_y() / screen.height * 2.0;
+    let sx = rect.width / screen.width;
+    let sy = rect.height / screen.height;
+
+    Matrix3x2::from_scale_rad_translate(sx, sy, rotation, tx, ty)
+}
+
+/// Signed distance to a circle centered at the origin, negative inside
+fn sd_circle(p: Vec2f, r: f32) -> f32 {
+    p.length() - r
+}
+
+/// Signed distance to a rounded rect centered at the origin, negative inside.
+/// The y axis points down, so the bottom corners are where `p.y > 0`
+pub(crate) fn sd_rounded_rect(p: Vec2f, half: Vec2f, corners: CornerRadius) -> f32 {
+    let r = match (p.x > 0.0, p.y > 0.0) {
+        (false, false) => corners.tl,
+        (false, true) => corners.bl,
+        (true, true) => corners.br,
+        (true, false) => corners.tr,
+    };
+    let d = Vec2f::new(p.x.abs(), p.y.abs()) - half + Vec2f::splat(r);
+    d.x.max(d.y).min(0.0) + d.max(Vec2f::splat(0.0)).length() - r
+}
+
 // creation
 impl WidgetState {
     pub fn new() -> Self {
@@ -143,22 +363,32 @@ impl WidgetState {
         self
     }
 
-    pub fn with_min_width(mut self, value: f32) -> Self {
+    pub fn with_width(mut self, width: impl Into<Length>) -> Self {
+        self.set_width(width);
+        self
+    }
+
+    pub fn with_height(mut self, height: impl Into<Length>) -> Self {
+        self.set_height(height);
+        self
+    }
+
+    pub fn with_min_width(mut self, value: impl Into<Length>) -> Self {
         self.set_min_width(value);
         self
     }
 
-    pub fn with_min_height(mut self, value: f32) -> Self {
+    pub fn with_min_height(mut self, value: impl Into<Length>) -> Self {
         self.set_min_height(value);
         self
     }
 
-    pub fn with_max_width(mut self, value: f32) -> Self {
+    pub fn with_max_width(mut self, value: impl Into<Length>) -> Self {
         self.set_max_width(value);
         self
     }
 
-    pub fn with_max_height(mut self, value: f32) -> Self {
+    pub fn with_max_height(mut self, value: impl Into<Length>) -> Self {
         self.set_max_height(value);
         self
     }
@@ -173,6 +403,11 @@ impl WidgetState {
         self
     }
 
+    pub fn with_justify(mut self, justify: Justify) -> Self {
+        self.set_justify(justify);
+        self
+    }
+
     pub fn with_orientation(mut self, orientation: Orientation) -> Self {
         self.set_orientation(orientation);
         self
@@ -183,11 +418,28 @@ impl WidgetState {
         self
     }
 
+    /// The lines are measured against the set size of the stack on the main axis,
+    /// otherwise the children are measured on a single line
+    pub fn with_wrap(mut self, wrap: bool) -> Self {
+        self.set_wrap(wrap);
+        self
+    }
+
+    pub fn with_line_spacing(mut self, value: f32) -> Self {
+        self.set_line_spacing(value);
+        self
+    }
+
     pub fn with_padding(mut self, value: Padding) -> Self {
         self.set_padding(value);
         self
     }
 
+    pub fn with_margin(mut self, value: Margin) -> Self {
+        self.set_margin(value);
+        self
+    }
+
     pub fn with_shape(mut self, shape: Shape) -> Self {
         self.set_shape(shape);
         self
@@ -228,6 +480,16 @@ impl WidgetState {
         self.set_dragable(drag);
         self
     }
+
+    pub fn with_focusable(mut self, focusable: bool) -> Self {
+        self.set_focusable(focusable);
+        self
+    }
+
+    pub fn with_clip(mut self, clip: bool) -> Self {
+        self.set_clip(clip);
+        self
+    }
 }
 
 // modifier
@@ -247,6 +509,11 @@ impl WidgetState {
         self.align_v = align_v;
     }
 
+    #[inline(always)]
+    pub fn set_justify(&mut self, justify: Justify) {
+        self.justify = justify;
+    }
+
     #[inline(always)]
     pub fn set_orientation(&mut self, orientation: Orientation) {
         self.orientation = orientation;
@@ -258,7 +525,30 @@ impl WidgetState {
     /// - (f32, f32)
     /// - [`Size`](aplite_types::Size)
     pub fn set_size(&mut self, size: impl Into<Size>) {
-        self.rect.set_size(size.into())
+        let size = size.into();
+        self.width = Length::Px(size.width);
+        self.height = Length::Px(size.height);
+        self.rect.set_size(size);
+    }
+
+    /// Types which implement [`Into<Length>`] are:
+    /// - f32 & u32, in pixels
+    /// - [`Length`](aplite_types::Length)
+    pub fn set_width(&mut self, width: impl Into<Length>) {
+        self.width = width.into();
+        if let Length::Px(value) = self.width {
+            self.rect.width = value;
+        }
+    }
+
+    /// Types which implement [`Into<Length>`] are:
+    /// - f32 & u32, in pixels
+    /// - [`Length`](aplite_types::Length)
+    pub fn set_height(&mut self, height: impl Into<Length>) {
+        self.height = height.into();
+        if let Length::Px(value) = self.height {
+            self.rect.height = value;
+        }
     }
 
     #[inline(always)]
@@ -267,23 +557,23 @@ impl WidgetState {
     }
 
     #[inline(always)]
-    pub fn set_min_width(&mut self, value: f32) {
-        self.min_width = Some(value)
+    pub fn set_min_width(&mut self, value: impl Into<Length>) {
+        self.min_width = Some(value.into())
     }
 
     #[inline(always)]
-    pub fn set_min_height(&mut self, value: f32) {
-        self.min_height = Some(value)
+    pub fn set_min_height(&mut self, value: impl Into<Length>) {
+        self.min_height = Some(value.into())
     }
 
     #[inline(always)]
-    pub fn set_max_width(&mut self, value: f32) {
-        self.max_width = Some(value)
+    pub fn set_max_width(&mut self, value: impl Into<Length>) {
+        self.max_width = Some(value.into())
     }
 
     #[inline(always)]
-    pub fn set_max_height(&mut self, value: f32) {
-        self.max_height = Some(value)
+    pub fn set_max_height(&mut self, value: impl Into<Length>) {
+        self.max_height = Some(value.into())
     }
 
     #[inline(always)]
@@ -291,11 +581,41 @@ impl WidgetState {
         self.padding = value;
     }
 
+    #[inline(always)]
+    pub fn set_margin(&mut self, value: Margin) {
+        self.margin = value;
+    }
+
     #[inline(always)]
     pub fn set_spacing(&mut self, value: f32) {
         self.spacing = value
     }
 
+    #[inline(always)]
+    pub fn set_flex_grow(&mut self, value: f32) {
+        self.flex_grow = value.max(0.0);
+    }
+
+    #[inline(always)]
+    pub fn set_flex_shrink(&mut self, value: f32) {
+        self.flex_shrink = value.max(0.0);
+    }
+
+    #[inline(always)]
+    pub fn set_flex_basis(&mut self, value: f32) {
+        self.flex_basis = Some(value.max(0.0));
+    }
+
+    #[inline(always)]
+    pub fn set_wrap(&mut self, wrap: bool) {
+        self.wrap = wrap;
+    }
+
+    #[inline(always)]
+    pub fn set_line_spacing(&mut self, value: f32) {
+        self.line_spacing = value;
+    }
+
     #[inline(always)]
     pub fn set_image_aspect_ratio(&mut self, aspect_ratio: AspectRatio) {
         self.image_aspect_ratio = aspect_ratio;
@@ -337,8 +657,76 @@ impl WidgetState {
         self.border_color = color.into();
     }
 
+    #[inline(always)]
+    pub fn set_border_width(&mut self, value: f32) {
+        self.border_width = value;
+    }
+
+    #[inline(always)]
+    pub fn set_hover_color(&mut self, color: Rgba<u8>) {
+        self.hover_color = Some(color);
+    }
+
+    #[inline(always)]
+    pub fn set_click_color(&mut self, color: Rgba<u8>) {
+        self.click_color = Some(color);
+    }
+
+    pub fn set_text(&mut self, content: String) {
+        self.text.get_or_insert_with(Default::default).content = content;
+        self.fit_text();
+    }
+
+    pub fn set_font_size(&mut self, font_size: f32) {
+        self.text.get_or_insert_with(Default::default).font_size = font_size;
+        self.fit_text();
+    }
+
+    pub fn set_font(&mut self, font: Font) {
+        self.text.get_or_insert_with(Default::default).font = Some(font);
+        self.fit_text();
+    }
+
+    #[inline(always)]
+    pub fn set_text_color(&mut self, color: Rgba<u8>) {
+        self.text.get_or_insert_with(Default::default).color = color;
+    }
+
+    /// Clamped to the scrollable range, only applies to a [`ScrollView`](crate::widget::ScrollView)
+    pub fn set_scroll_offset(&mut self, offset: Vec2f) {
+        let viewport = self.rect.size();
+        if let Some(scroll) = self.scroll.as_mut() {
+            scroll.offset = scroll.clamp(offset, viewport);
+        }
+    }
+
+    #[inline(always)]
+    pub fn set_grid_cell(&mut self, cell: GridCell) {
+        self.grid_cell = Some(cell);
+    }
+
     #[inline(always)]
     pub fn set_dragable(&mut self, drag: bool) {
         self.dragable = drag;
     }
+
+    #[inline(always)]
+    pub fn set_z_index(&mut self, value: i32) {
+        self.z_index = value;
+    }
+
+    #[inline(always)]
+    pub fn set_cursor_icon(&mut self, icon: CursorIcon) {
+        self.cursor_icon = Some(icon);
+    }
+
+    #[inline(always)]
+    pub fn set_focusable(&mut self, focusable: bool) {
+        self.focusable = focusable;
+    }
+
+    #[inline(always)]
+    pub fn set_clip(&mut self, clip: bool) {
+        self.clip = clip;
+    }
 }
@@ -1,45 +1,66 @@
 use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::mpsc::{channel, Sender, Receiver};
 
 use aplite_reactive::*;
 use aplite_renderer::Shape;
-use aplite_storage::{Tree, U64Map};
+use aplite_storage::{SpatialGrid, Tree, U64Map};
 use aplite_types::{
     CornerRadius,
+    Length,
     Paint,
     Size,
 };
 
-use crate::widget::Widget;
+use crate::widget::{Widget, CALLBACKS, reconcile_children, remove_popups, remove_reconcilers, remove_tooltips};
 use crate::state::WidgetState;
+use crate::context::cursor::CursorIcon;
 use crate::context::layout::{
     Orientation,
     AlignH,
     AlignV,
+    Justify,
+    relayout,
 };
 
 aplite_macro::entity! { pub ViewId }
 
+/// The size of the cells of the hover index, in logical pixels
+const HOVER_CELL_SIZE: f32 = 64.0;
+
 // FIXME: this is kinda cheating, and not fun at all
 thread_local! {
     pub(crate) static VIEW_STORAGE: ViewStorage = ViewStorage::new();
 }
 
+/// A deferred modification of a [`WidgetState`], produced by an [`Effect`] on the executor thread
+pub(crate) type StateUpdate = (ViewId, Box<dyn FnOnce(&mut WidgetState) + Send>);
+
 pub(crate) struct ViewStorage {
     pub(crate) tree: RefCell<Tree<ViewId, WidgetState>>,
     pub(crate) storage: RefCell<U64Map<ViewId, View>>,
 
-    // WARN: do you really need separate id for paint?
-    pub(crate) hoverable: RefCell<Vec<ViewId>>,
+    /// The hoverable views, indexed by their bounds
+    pub(crate) hoverable: RefCell<SpatialGrid<ViewId>>,
+    pub(crate) effects: RefCell<U64Map<ViewId, Vec<Effect>>>,
     pub(crate) dirty: Signal<bool>,
+
+    // effects are running on the executor's thread, so they can't touch the tree directly
+    update_tx: Sender<StateUpdate>,
+    update_rx: Receiver<StateUpdate>,
 }
 
 impl ViewStorage {
     fn new() -> Self {
+        let (update_tx, update_rx) = channel();
         Self {
             tree: RefCell::new(Tree::with_capacity(1024)),
             storage: RefCell::new(U64Map::new()),
-            hoverable: RefCell::new(Vec::new()),
+            hoverable: RefCell::new(SpatialGrid::new(HOVER_CELL_SIZE)),
+            effects: RefCell::new(U64Map::new()),
             dirty: Signal::new(false),
+            update_tx,
+            update_rx,
         }
     }
 
@@ -47,6 +68,66 @@ impl ViewStorage {
         self.tree.borrow_mut().insert(data)
     }
 
+    pub(crate) fn update_sender(&self) -> Sender<StateUpdate> {
+        self.update_tx.clone()
+    }
+
+    /// Apply every [`StateUpdate`] sent by the effects since the last call.
+    /// Returns the ids of the modified [`WidgetState`]s, and of the ones whose size has changed
+    pub(crate) fn apply_pending_updates(&self) -> (Vec<ViewId>, Vec<ViewId>) {
+        let mut tree = self.tree.borrow_mut();
+        let mut updated = Vec::new();
+        let mut resized = Vec::new();
+
+        while let Ok((id, update)) = self.update_rx.try_recv() {
+            if let Some(state) = tree.get_mut(&id) {
+                let size = state.rect.size();
+                update(state);
+                self.update_bounds(&id, state);
+                if state.rect.size() != size && !resized.contains(&id) {
+                    resized.push(id);
+                }
+                updated.push(id);
+            }
+        }
+
+        (updated, resized)
+    }
+
+    pub(crate) fn add_effect(&self, id: ViewId, effect: Effect) {
+        let mut effects = self.effects.borrow_mut();
+        match effects.get_mut(&id) {
+            Some(owned) => owned.push(effect),
+            None => { effects.insert(id, vec![effect]); },
+        }
+    }
+
+    pub(crate) fn set_hoverable(&self, id: &ViewId) {
+        if let Some(state) = self.tree.borrow_mut().get_mut(id) {
+            state.hoverable = true;
+            self.update_bounds(id, state);
+        }
+    }
+
+    /// Keep the hover index in sync after the rect of the view has moved or resized
+    pub(crate) fn update_bounds(&self, id: &ViewId, state: &WidgetState) {
+        if state.hoverable {
+            self.hoverable.borrow_mut().insert(*id, state.hit_bounds());
+        }
+    }
+
+    /// Index the whole subtree, which may have been built before being attached
+    fn update_subtree_bounds(&self, id: &ViewId) {
+        let tree = self.tree.borrow();
+        std::iter::once(*id)
+            .chain(tree.get_all_members_of(id))
+            .for_each(|id| {
+                if let Some(state) = tree.get(&id) {
+                    self.update_bounds(&id, state);
+                }
+            });
+    }
+
     // FIXME: there's logic error when appending on a fn() -> impl IntoView
     pub(crate) fn append_child(&self, id: &ViewId, child: impl IntoView) {
         let child_id = child.id();
@@ -56,6 +137,7 @@ impl ViewStorage {
 
         self.tree.borrow_mut().add_child(id, child_id);
         self.storage.borrow_mut().insert(child_id, child.into_view());
+        self.update_subtree_bounds(&child_id);
     }
 
     pub(crate) fn add_sibling(&self, id: &ViewId, sibling: impl IntoView) {
@@ -66,6 +148,38 @@ impl ViewStorage {
 
         self.tree.borrow_mut().add_sibling(id, sibling_id);
         self.storage.borrow_mut().insert(sibling_id, sibling.into_view());
+        self.update_subtree_bounds(&sibling_id);
+    }
+
+    /// Remove the view and its whole subtree, and release everything registered for them:
+    /// the callbacks, the effects, and the children reconcilers.
+    /// The images are dropped with the [`WidgetState`], and the renderer frees their atlas space
+    pub(crate) fn remove(&self, id: &ViewId) {
+        self.remove_all(std::slice::from_ref(id));
+    }
+
+    /// Same as [`remove`](Self::remove) for each view, going through the registries once
+    pub(crate) fn remove_all(&self, ids: &[ViewId]) {
+        let removed = {
+            let mut tree = self.tree.borrow_mut();
+            ids.iter().flat_map(|id| tree.remove(*id)).collect::<HashSet<_>>()
+        };
+
+        let mut storage = self.storage.borrow_mut();
+        let mut effects = self.effects.borrow_mut();
+        removed.iter().for_each(|id| {
+            storage.remove(id);
+            if let Some(owned) = effects.remove(id) {
+                owned.iter().for_each(Effect::dispose);
+            }
+        });
+
+        CALLBACKS.with(|cb| cb.borrow_mut().retain(|id, _| !removed.contains(id)));
+        remove_reconcilers(&removed);
+        remove_tooltips(&removed);
+        remove_popups(&removed);
+        let mut hoverable = self.hoverable.borrow_mut();
+        removed.iter().for_each(|id| hoverable.remove(id));
     }
 
     #[inline(always)]
@@ -74,6 +188,24 @@ impl ViewStorage {
     }
 }
 
+/// Apply the updates sent by the effects, rebuild the children of the reconciled views,
+/// and lay out again around the ones whose size or children have changed.
+/// Returns the ids of the updated views
+pub(crate) fn update_views() -> Vec<ViewId> {
+    let (updated, mut changed) = VIEW_STORAGE.with(|s| s.apply_pending_updates());
+    changed.extend(reconcile_children(&updated));
+    relayout(&changed);
+
+    updated
+}
+
+/// Wait for the effects to run on the executor, then apply their updates as a redraw does
+#[cfg(test)]
+pub(crate) fn run_effects() -> Vec<ViewId> {
+    aplite_future::Executor::wait_idle();
+    update_views()
+}
+
 pub trait IntoView: Widget {
     fn into_view(self) -> View;
 }
@@ -116,6 +248,12 @@ impl View {
 #[derive(Clone, Copy, Debug)]
 pub struct ViewNode(pub(crate) ViewId);
 
+impl Default for ViewNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl ViewNode {
     pub fn new() -> Self {
         VIEW_STORAGE.with(|s| {
@@ -164,41 +302,61 @@ impl ViewNode {
         self
     }
 
-    pub fn with_min_width(self, val: f32) -> Self {
+    pub fn with_width(self, width: impl Into<Length>) -> Self {
         VIEW_STORAGE.with(|s| {
             let mut tree = s.tree.borrow_mut();
             if let Some(state) = tree.get_mut(&self.0) {
-                state.min_width = Some(val);
+                state.set_width(width);
             }
         });
         self
     }
 
-    pub fn with_max_width(self, val: f32) -> Self {
+    pub fn with_height(self, height: impl Into<Length>) -> Self {
         VIEW_STORAGE.with(|s| {
             let mut tree = s.tree.borrow_mut();
             if let Some(state) = tree.get_mut(&self.0) {
-                state.max_width = Some(val);
+                state.set_height(height);
             }
         });
         self
     }
 
-    pub fn with_min_height(self, val: f32) -> Self {
+    pub fn with_min_width(self, val: impl Into<Length>) -> Self {
         VIEW_STORAGE.with(|s| {
             let mut tree = s.tree.borrow_mut();
             if let Some(state) = tree.get_mut(&self.0) {
-                state.min_height = Some(val);
+                state.min_width = Some(val.into());
             }
         });
         self
     }
 
-    pub fn with_max_height(self, val: f32) -> Self {
+    pub fn with_max_width(self, val: impl Into<Length>) -> Self {
         VIEW_STORAGE.with(|s| {
             let mut tree = s.tree.borrow_mut();
             if let Some(state) = tree.get_mut(&self.0) {
-                state.max_height = Some(val);
+                state.max_width = Some(val.into());
+            }
+        });
+        self
+    }
+
+    pub fn with_min_height(self, val: impl Into<Length>) -> Self {
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            if let Some(state) = tree.get_mut(&self.0) {
+                state.min_height = Some(val.into());
+            }
+        });
+        self
+    }
+
+    pub fn with_max_height(self, val: impl Into<Length>) -> Self {
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            if let Some(state) = tree.get_mut(&self.0) {
+                state.max_height = Some(val.into());
             }
         });
         self
@@ -300,6 +458,16 @@ impl ViewNode {
         self
     }
 
+    pub fn with_justify(self, justify: Justify) -> Self {
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            if let Some(state) = tree.get_mut(&self.0) {
+                state.justify = justify;
+            }
+        });
+        self
+    }
+
     pub fn with_orientation(self, orientation: Orientation) -> Self {
         VIEW_STORAGE.with(|s| {
             let mut tree = s.tree.borrow_mut();
@@ -310,14 +478,105 @@ impl ViewNode {
         self
     }
 
-    pub fn set_hoverable(self) -> Self {
+    pub fn with_cursor_icon(self, icon: CursorIcon) -> Self {
         VIEW_STORAGE.with(|s| {
             let mut tree = s.tree.borrow_mut();
             if let Some(state) = tree.get_mut(&self.0) {
-                state.hoverable = true;
+                state.set_cursor_icon(icon);
             }
-            s.hoverable.borrow_mut().push(self.0);
         });
         self
     }
+
+    pub fn set_hoverable(self) -> Self {
+        VIEW_STORAGE.with(|s| s.set_hoverable(&self.0));
+        self
+    }
+}
+
+#[cfg(test)]
+mod view_test {
+    use aplite_future::Executor;
+    use aplite_types::Rgba;
+    use crate::widget::{Button, RECONCILERS, VStack, WidgetEvent, WidgetExt, for_each};
+    use super::*;
+
+    #[test]
+    fn remove_leaves_no_dangling_ids() {
+        Executor::init();
+
+        let items = Signal::new(vec![1u32, 2]);
+        let list = for_each(items.clone(), |n| *n, |_| Button::new().on(WidgetEvent::LeftClick, || {}));
+        let stack = VStack::new()
+            .child(Button::new().color(|_| Rgba::RED).hover_color(|| Rgba::GREEN))
+            .child(list);
+        let stack_id = stack.id();
+        let parent = VStack::new().child(stack);
+        let parent_id = parent.id();
+
+        let (ids, effects) = VIEW_STORAGE.with(|s| {
+            let mut ids = s.get_all_members_of(&stack_id);
+            ids.push(stack_id);
+            let effects = s.effects
+                .borrow()
+                .iter()
+                .filter(|(id, _)| ids.contains(id))
+                .flat_map(|(_, effects)| effects.clone())
+                .collect::<Vec<_>>();
+            (ids, effects)
+        });
+        assert_eq!(ids.len(), 5);
+        assert!(!effects.is_empty());
+
+        VIEW_STORAGE.with(|s| s.remove(&stack_id));
+
+        VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            let storage = s.storage.borrow();
+            let hoverable = s.hoverable.borrow();
+            let owned = s.effects.borrow();
+            ids.iter().for_each(|id| {
+                assert!(!tree.contains(id));
+                assert!(!storage.contains_key(id));
+                assert!(!hoverable.contains(id));
+                assert!(!owned.contains_key(id));
+            });
+            assert!(tree.contains(&parent_id));
+            assert!(tree.get_all_children(&parent_id).is_none());
+        });
+        ids.iter().for_each(|id| {
+            assert!(CALLBACKS.with(|cb| !cb.borrow().contains_key(id)));
+            assert!(RECONCILERS.with(|r| !r.borrow().contains_key(id)));
+        });
+        assert!(effects.iter().all(Effect::is_disposed));
+
+        // removing a stale id doesn't touch the views reusing its slot
+        let button = Button::new();
+        VIEW_STORAGE.with(|s| {
+            s.remove(&stack_id);
+            assert!(s.tree.borrow().contains(&button.id()));
+        });
+    }
+
+    #[test]
+    fn remove_all_releases_every_subtree() {
+        let buttons = [(); 3].map(|_| Button::new().on(WidgetEvent::LeftClick, || {}));
+        let ids = buttons.each_ref().map(Widget::id);
+        let stack = buttons.into_iter().fold(VStack::new(), |stack, button| stack.child(button));
+        let stack_id = stack.id();
+
+        // the same subtree may be given twice
+        VIEW_STORAGE.with(|s| s.remove_all(&[ids[0], ids[2], ids[2]]));
+
+        VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            assert_eq!(tree.get_all_children(&stack_id), Some(vec![ids[1]]));
+            assert!(!tree.contains(&ids[0]) && !tree.contains(&ids[2]));
+        });
+        CALLBACKS.with(|cb| {
+            let cb = cb.borrow();
+            assert!(cb.contains_key(&ids[1]));
+            assert!(!cb.contains_key(&ids[0]) && !cb.contains_key(&ids[2]));
+        });
+    }
 }
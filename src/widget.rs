@@ -1,49 +1,158 @@
 use std::cell::RefCell;
 use std::collections::HashMap;
 
+use aplite_reactive::*;
 use aplite_renderer::Shape;
 use aplite_storage::U64Map;
-use aplite_types::{Rgba, CornerRadius, Size};
+use aplite_types::{Rgba, CornerRadius, Length, Size};
 
+use crate::context::cursor::{CursorIcon, MouseEvent};
+use crate::context::keyboard::KeyEvent;
+use crate::context::layout::Margin;
 use crate::state::WidgetState;
 use crate::view::{
     IntoView,
     ViewId,
     ViewNode,
+    ViewStorage,
     VIEW_STORAGE,
 };
 
 mod button;
+mod grid;
 mod image;
+mod list;
+mod popup;
+mod scroll;
+mod show;
 mod stack;
+mod text;
+mod text_input;
+mod tooltip;
 
 pub use {
     button::*,
+    grid::{Grid, GridCell, Track, grid},
     image::*,
+    list::{For, for_each},
+    popup::{Placement, Popup, popup},
+    scroll::{ScrollView, scroll_view},
+    show::{Show, Switch, show, switch},
     stack::*,
+    text::{Text, text},
+    text_input::{TextInput, text_input},
+    tooltip::IntoTooltip,
 };
 
+pub(crate) use grid::GridState;
+pub(crate) use list::{reconcile_children, remove_reconcilers};
+pub(crate) use popup::{PopupOptions, call_on_dismiss, create_popup, remove_popups, take_popup_requests};
+#[cfg(test)] pub(crate) use list::RECONCILERS;
+pub(crate) use scroll::{ScrollState, SCROLLBAR_COLOR};
+pub(crate) use text::{TextState, SELECTION_COLOR, set_default_font};
+pub(crate) use tooltip::{create_tooltip, has_tooltip, remove_tooltips};
+
 thread_local! {
     pub(crate) static CALLBACKS: RefCell<Callbacks> = RefCell::new(Default::default());
 }
 
 type Callbacks = HashMap<ViewId, WidgetCallback>;
 
-pub(crate) struct WidgetCallback(U64Map<WidgetEvent, Box<dyn FnMut()>>);
+/// The callbacks of a widget. Dereferences to the bubbling phase ones
+pub(crate) struct WidgetCallback {
+    bubble: U64Map<WidgetEvent, Callback>,
+    pub(crate) capture: U64Map<WidgetEvent, Callback>,
+}
+
+pub enum Callback {
+    Action(Box<dyn FnMut()>),
+    Mouse(Box<dyn FnMut(&MouseEvent)>),
+    Key(Box<dyn FnMut(&KeyEvent)>),
+}
+
+impl Callback {
+    pub(crate) fn call_with_mouse(&mut self, event: &MouseEvent) {
+        match self {
+            Self::Action(f) => f(),
+            Self::Mouse(f) => f(event),
+            Self::Key(_) => {},
+        }
+    }
+
+    pub(crate) fn call_with_key(&mut self, event: &KeyEvent) {
+        match self {
+            Self::Action(f) => f(),
+            Self::Key(f) => f(event),
+            Self::Mouse(_) => {},
+        }
+    }
+}
 
+/// Types which can be registered via [`WidgetExt::on`]:
+/// - `FnMut()`
+/// - `FnMut(&MouseEvent)`, only called on the mouse events.
+/// - `FnMut(&KeyEvent)`, only called on [`WidgetEvent::Input`].
+///
+/// The argument type needs to be annotated, e.g. `|key: &KeyEvent| { .. }`
+pub trait IntoCallback<Args> {
+    fn into_callback(self) -> Callback;
+}
+
+impl<F: FnMut() + 'static> IntoCallback<()> for F {
+    fn into_callback(self) -> Callback {
+        Callback::Action(Box::new(self))
+    }
+}
+
+impl<F: FnMut(&MouseEvent) + 'static> IntoCallback<MouseEvent> for F {
+    fn into_callback(self) -> Callback {
+        Callback::Mouse(Box::new(self))
+    }
+}
+
+impl<F: FnMut(&KeyEvent) + 'static> IntoCallback<KeyEvent> for F {
+    fn into_callback(self) -> Callback {
+        Callback::Key(Box::new(self))
+    }
+}
+
+/// The click events are sent on release, to the widget which was pressed.
+/// A release which ends a drag only sends [`WidgetEvent::DragEnd`], and one which ends a long press sends nothing.
+/// The second left click in a row sends [`WidgetEvent::DoubleClick`] after [`WidgetEvent::LeftClick`].
+/// [`WidgetEvent::LongPress`] is sent while the left button is still held.
+///
+/// The click & drag events propagate through the ancestors of the target:
+/// first the capture phase from the root down to the target, then the bubbling phase back up to the root.
+/// See [`MouseEvent::stop_propagation`]
 #[repr(u64)]
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum WidgetEvent {
-    Hover,
+    HoverEnter,
+    HoverLeave,
     LeftClick,
     RightClick,
-    Drag,
+    MiddleClick,
+    DoubleClick,
+    LongPress,
+    DragStart,
+    DragMove,
+    DragEnd,
     Input,
 }
 
+impl WidgetEvent {
+    /// Whether the event propagates to the ancestors of the target
+    pub fn bubbles(&self) -> bool {
+        !matches!(self, Self::HoverEnter | Self::HoverLeave | Self::Input)
+    }
+}
+
 impl Default for WidgetCallback {
     fn default() -> Self {
-        Self(U64Map::with_capacity(5))
+        Self {
+            bubble: U64Map::with_capacity(11),
+            capture: U64Map::new(),
+        }
     }
 }
 
@@ -70,15 +179,35 @@ pub trait WidgetExt: Widget + Sized {
         self
     }
 
-    fn on<F>(self, event: WidgetEvent, f: F) -> Self
+    /// Registering a callback for [`WidgetEvent::Input`] also makes the widget focusable
+    fn on<F, Args>(self, event: WidgetEvent, f: F) -> Self
     where
-        F: FnMut() + 'static,
+        F: IntoCallback<Args>,
     {
         CALLBACKS.with(|cell| {
             let mut storage = cell.borrow_mut();
             let callbacks = storage.entry(self.id()).or_default();
-            callbacks.insert(event, Box::new(f));
+            callbacks.insert(event, f.into_callback());
         });
+        VIEW_STORAGE.with(|s| s.set_hoverable(&self.id()));
+        if event == WidgetEvent::Input {
+            return self.focusable(true);
+        }
+        self
+    }
+
+    /// Registers a callback for the capture phase, which runs before the descendants receive the event.
+    /// Only the events which [bubble](WidgetEvent::bubbles) have a capture phase
+    fn on_capture<F, Args>(self, event: WidgetEvent, f: F) -> Self
+    where
+        F: IntoCallback<Args>,
+    {
+        CALLBACKS.with(|cell| {
+            let mut storage = cell.borrow_mut();
+            let callbacks = storage.entry(self.id()).or_default();
+            callbacks.capture.insert(event, f.into_callback());
+        });
+        VIEW_STORAGE.with(|s| s.set_hoverable(&self.id()));
         self
     }
 
@@ -99,7 +228,7 @@ pub trait WidgetExt: Widget + Sized {
     where
         F: FnEl<Rgba<u8>> + 'static,
     {
-        let _ = f;
+        bind_state(self.id(), f, |state, color| state.set_background(color));
         self
     }
 
@@ -107,23 +236,25 @@ pub trait WidgetExt: Widget + Sized {
     where
         F: FnEl<Rgba<u8>> + 'static
     {
-        let _ = f;
+        bind_state(self.id(), f, |state, color| state.set_border_color(color));
         self
     }
 
-    fn hover_color<F>(self, f: F) -> Self
+    fn hover_color<F>(self, mut f: F) -> Self
     where
         F: FnAction<Rgba<u8>> + 'static
     {
-        let _ = f;
+        VIEW_STORAGE.with(|s| s.set_hoverable(&self.id()));
+        bind_state(self.id(), move |_| f(), WidgetState::set_hover_color);
         self
     }
 
-    fn click_color<F>(self, f: F) -> Self
+    fn click_color<F>(self, mut f: F) -> Self
     where
         F: FnAction<Rgba<u8>> + 'static,
     {
-        let _ = f;
+        VIEW_STORAGE.with(|s| s.set_hoverable(&self.id()));
+        bind_state(self.id(), move |_| f(), WidgetState::set_click_color);
         self
     }
 
@@ -131,15 +262,16 @@ pub trait WidgetExt: Widget + Sized {
     where
         F: FnEl<u32> + 'static
     {
-        let _ = f;
+        bind_state(self.id(), f, |state, width| state.set_border_width(width as f32));
         self
     }
 
+    /// the value is in degrees
     fn rotation<F>(self, f: F) -> Self
     where
         F: FnEl<f32> + 'static
     {
-        let _ = f;
+        bind_state(self.id(), f, WidgetState::set_rotation_deg);
         self
     }
 
@@ -147,7 +279,7 @@ pub trait WidgetExt: Widget + Sized {
     where
         F: FnEl<CornerRadius> + 'static
     {
-        let _ = f;
+        bind_state(self.id(), f, WidgetState::set_corner_radius);
         self
     }
 
@@ -155,7 +287,7 @@ pub trait WidgetExt: Widget + Sized {
     where
         F: FnEl<Shape> + 'static
     {
-        let _ = f;
+        bind_state(self.id(), f, WidgetState::set_shape);
         self
     }
 
@@ -163,21 +295,181 @@ pub trait WidgetExt: Widget + Sized {
         VIEW_STORAGE.with(|s| {
             let mut tree = s.tree.borrow_mut();
             let state = tree.get_mut(&self.id()).unwrap();
-            state.rect.set_size(size.into());
+            state.set_size(size);
         });
         self
     }
 
+    /// Types which implement [`Into<Length>`] are:
+    /// - f32 & u32, in pixels
+    /// - [`Length`], which may be relative to the parent
+    fn width(self, width: impl Into<Length>) -> Self {
+        let width = width.into();
+        self.set_state(|state| state.set_width(width))
+    }
+
+    /// Types which implement [`Into<Length>`] are:
+    /// - f32 & u32, in pixels
+    /// - [`Length`], which may be relative to the parent
+    fn height(self, height: impl Into<Length>) -> Self {
+        let height = height.into();
+        self.set_state(|state| state.set_height(height))
+    }
+
+    /// A dragable widget shows the [`Grab`](CursorIcon::Grab) cursor, unless it already has an icon
     fn dragable(self, value: bool) -> Self {
         VIEW_STORAGE.with(|s| {
             let mut tree = s.tree.borrow_mut();
             let state = tree.get_mut(&self.id()).unwrap();
             state.dragable = value;
+            if value && state.cursor_icon.is_none() {
+                state.cursor_icon = Some(CursorIcon::Grab);
+            }
+        });
+        self
+    }
+
+    /// Shown near the cursor once the widget has been hovered for the
+    /// [`tooltip_delay`](crate::context::cursor::ClickSettings::tooltip_delay),
+    /// and hidden when the cursor leaves or clicks
+    fn tooltip<T, Args>(self, content: T) -> Self
+    where
+        T: IntoTooltip<Args>,
+    {
+        tooltip::register_tooltip(self.id(), content.into_tooltip());
+        VIEW_STORAGE.with(|s| s.set_hoverable(&self.id()));
+        self
+    }
+
+    /// Anchor the [`Popup`] to this widget. Only one popup can be anchored to a widget
+    fn popup(self, popup: Popup) -> Self {
+        popup::register_popup(self.id(), popup);
+        self
+    }
+
+    /// The icon of the mouse cursor while the widget is hovered
+    fn cursor_icon(self, icon: CursorIcon) -> Self {
+        VIEW_STORAGE.with(|s| {
+            if let Some(state) = s.tree.borrow_mut().get_mut(&self.id()) {
+                state.set_cursor_icon(icon);
+            }
+            s.set_hoverable(&self.id());
+        });
+        self
+    }
+
+    /// Focusable widgets receive the keyboard input, and can be reached with Tab & Shift+Tab
+    fn focusable(self, value: bool) -> Self {
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            let state = tree.get_mut(&self.id()).unwrap();
+            state.focusable = value;
+        });
+        self
+    }
+
+    /// Grow into the free space on the main axis of the parent stack,
+    /// shared between the siblings relative to their own factor
+    fn flex_grow(self, value: f32) -> Self {
+        self.set_state(|state| state.set_flex_grow(value))
+    }
+
+    /// Give back the space overflowing the main axis of the parent stack,
+    /// shared between the siblings relative to their own factor & basis. Zero by default
+    fn flex_shrink(self, value: f32) -> Self {
+        self.set_state(|state| state.set_flex_shrink(value))
+    }
+
+    /// The size on the main axis of the parent stack before growing or shrinking,
+    /// instead of the measured size
+    fn flex_basis(self, value: f32) -> Self {
+        self.set_state(|state| state.set_flex_basis(value))
+    }
+
+    /// The space kept around the widget inside its parent.
+    /// The auto sides take the free space, so [`Margin::auto`] centers the widget
+    fn margin(self, margin: Margin) -> Self {
+        self.set_state(|state| state.set_margin(margin))
+    }
+
+    /// The cell of the widget inside its parent [`Grid`]
+    fn grid_cell(self, cell: GridCell) -> Self {
+        self.set_state(|state| state.set_grid_cell(cell))
+    }
+
+    /// Siblings with a higher z-index are drawn above, and hovered first.
+    /// Siblings with the same z-index keep their order
+    fn z_index(self, value: i32) -> Self {
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            let state = tree.get_mut(&self.id()).unwrap();
+            state.set_z_index(value);
+        });
+        self
+    }
+
+    /// Children overflowing the widget's shape are cut off, and can't be hovered outside of it
+    fn clip(self, value: bool) -> Self {
+        VIEW_STORAGE.with(|s| {
+            let mut tree = s.tree.borrow_mut();
+            let state = tree.get_mut(&self.id()).unwrap();
+            state.clip = value;
         });
         self
     }
 }
 
+/// Run `f` inside an [`Effect`] owned by the view, and send the result to be applied
+/// into the view's [`WidgetState`] on the next redraw
+fn bind_state<T, F>(id: ViewId, f: F, apply: fn(&mut WidgetState, T))
+where
+    T: Clone + Send + 'static,
+    F: FnEl<T> + 'static,
+{
+    VIEW_STORAGE.with(|s| {
+        let effect = Effect::new(send_state(s, id, f, apply));
+        s.add_effect(id, effect);
+    });
+}
+
+/// Same as [`bind_state`], but the closure runs right away, and its first value is returned
+/// for the state needed by the first layout
+fn bind_state_eager<T, F>(id: ViewId, f: F, apply: fn(&mut WidgetState, T)) -> T
+where
+    T: Clone + Send + 'static,
+    F: FnEl<T> + 'static,
+{
+    VIEW_STORAGE.with(|s| {
+        let (effect, value) = Effect::new_eager(send_state(s, id, f, apply));
+        s.add_effect(id, effect);
+        value
+    })
+}
+
+/// Wrap `f` to send each of its values to be applied into the view's [`WidgetState`]
+fn send_state<T, F>(
+    s: &ViewStorage,
+    id: ViewId,
+    mut f: F,
+    apply: fn(&mut WidgetState, T),
+) -> impl FnMut(Option<T>) -> T + 'static
+where
+    T: Clone + Send + 'static,
+    F: FnEl<T> + 'static,
+{
+    let tx = s.update_sender();
+    let dirty = s.dirty;
+
+    move |prev| {
+        let value = f(prev);
+        let next = value.clone();
+        if tx.send((id, Box::new(move |state| apply(state, next)))).is_ok() {
+            dirty.set(true);
+        }
+        value
+    }
+}
+
 /// this is just a wrapper over `FnMut(Option<T>) -> T`
 pub trait FnEl<T>: FnMut(Option<T>) -> T {}
 
@@ -189,14 +481,14 @@ pub trait FnAction<T>: FnMut() -> T {}
 impl<F, T> FnAction<T> for F where F: FnMut() -> T {}
 
 impl std::ops::Deref for WidgetCallback {
-    type Target = U64Map<WidgetEvent, Box<dyn FnMut()>>;
+    type Target = U64Map<WidgetEvent, Callback>;
 
-    fn deref(&self) -> &Self::Target { &self.0 }
+    fn deref(&self) -> &Self::Target { &self.bubble }
 }
 
 impl std::ops::DerefMut for WidgetCallback {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut self.bubble
     }
 }
 
@@ -210,6 +502,12 @@ pub struct CircleWidget {
     node: ViewNode,
 }
 
+impl Default for CircleWidget {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl CircleWidget {
     pub fn new() -> Self {
         let node = ViewNode::new()
@@ -229,3 +527,91 @@ impl Widget for CircleWidget {
         self.node
     }
 }
+
+#[cfg(test)]
+mod widget_test {
+    use std::sync::Arc;
+
+    use aplite_future::Executor;
+    use aplite_renderer::Font;
+    use aplite_types::Paint;
+    use crate::context::Context;
+    use crate::context::layout::{LayoutContext, calculate_size_recursive};
+    use crate::view::run_effects;
+    use super::*;
+
+    #[test]
+    fn reactive_style() {
+        Executor::init();
+
+        let (color, set_color) = Signal::split(Rgba::RED);
+        let (width, set_width) = Signal::split(1u32);
+
+        let id = Button::new()
+            .color(move |_| color.get())
+            .border_width(move |_| width.get())
+            .hover_color(|| Rgba::GREEN)
+            .id();
+
+        let state_of = || VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            let state = tree.get(&id).unwrap();
+            (state.background.clone(), state.border_width, state.hover_color)
+        });
+
+        run_effects();
+        assert!(state_of() == (Paint::Color(Rgba::RED), 1.0, Some(Rgba::GREEN)));
+
+        set_color.set(Rgba::BLUE);
+        set_width.set(5);
+
+        run_effects();
+        assert!(state_of() == (Paint::Color(Rgba::BLUE), 5.0, Some(Rgba::GREEN)));
+        assert!(Context::dirty().get_untracked());
+    }
+
+    #[test]
+    fn text_fits_content() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        Executor::init();
+
+        let font = Font::from_bytes(include_bytes!("../crates/aplite_renderer/assets/DejaVuSans-Ascii.ttf"))
+            .expect("the test font should load");
+
+        let (content, set_content) = Signal::split(String::from("hi"));
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&runs);
+        let label = text(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+            content.get()
+        })
+        .font(font.clone());
+        let id = label.id();
+        let sibling = Button::new().size((10, 10));
+        let sibling_id = sibling.id();
+        let row = HStack::new().child(label).child(sibling);
+        let row_id = row.id();
+
+        calculate_size_recursive(&row_id);
+        LayoutContext::new(row_id).calculate();
+        let rect_of = |id: &ViewId| VIEW_STORAGE.with(|s| s.tree.borrow().get(id).unwrap().rect);
+
+        // the content is read once for the first layout, and not again until it changes
+        let width = font.measure("hi", 16.0).width;
+        assert_eq!(rect_of(&id).width, width);
+        assert_eq!(rect_of(&sibling_id).x, width);
+        run_effects();
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+
+        set_content.set("hello world".to_string());
+        run_effects();
+        assert_eq!(runs.load(Ordering::Relaxed), 2);
+
+        // the row grows with the text, and the sibling moves after it
+        let width = font.measure("hello world", 16.0).width;
+        assert_eq!(rect_of(&id).width, width);
+        assert_eq!(rect_of(&sibling_id).x, width);
+        assert_eq!(rect_of(&row_id).width, width + 10.);
+    }
+}
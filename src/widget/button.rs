@@ -1,4 +1,6 @@
 use aplite_renderer::Shape;
+use crate::context::cursor::CursorIcon;
+
 use super::{ViewNode, Widget};
 
 pub fn button() -> Button { Button::new() }
@@ -7,12 +9,19 @@ pub struct Button {
     node: ViewNode,
 }
 
+impl Default for Button {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl Button {
     pub fn new() -> Self {
         let node = ViewNode::new()
             .with_name("Button")
             .with_shape(Shape::RoundedRect)
             .set_hoverable()
+            .with_cursor_icon(CursorIcon::Pointer)
             .with_size((80, 30));
 
         Self {
@@ -23,6 +32,6 @@ impl Button {
 
 impl Widget for Button {
     fn node(&self) -> ViewNode {
-        self.node.clone()
+        self.node
     }
 }
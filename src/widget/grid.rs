@@ -0,0 +1,409 @@
+use std::collections::HashSet;
+
+use aplite_renderer::Shape;
+use aplite_types::{Rect, Rgba, Size};
+
+use crate::context::layout::{AlignH, AlignV};
+
+use super::{ViewNode, Widget, WidgetExt};
+
+pub fn grid() -> Grid {
+    Grid::new()
+}
+
+/// The size of a row or a column of a [`Grid`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Track {
+    /// In logical pixels
+    Fixed(f32),
+    /// Fits the largest child inside the track
+    Auto,
+    /// A share of the space left by the other tracks, relative to the other fractions.
+    /// It's never smaller than needed to fit its children
+    Fraction(f32),
+}
+
+/// The placement of a child inside a [`Grid`], set with [`WidgetExt::grid_cell`].
+/// The rows & columns are zero based, the children without a cell fill the free cells row by row
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GridCell {
+    pub(crate) row: usize,
+    pub(crate) column: usize,
+    pub(crate) row_span: usize,
+    pub(crate) column_span: usize,
+    pub(crate) align_h: Option<AlignH>,
+    pub(crate) align_v: Option<AlignV>,
+}
+
+impl GridCell {
+    pub fn new(row: usize, column: usize) -> Self {
+        Self {
+            row,
+            column,
+            row_span: 1,
+            column_span: 1,
+            align_h: None,
+            align_v: None,
+        }
+    }
+
+    /// The number of rows & columns covered by the child, at least one
+    pub fn span(mut self, rows: usize, columns: usize) -> Self {
+        self.row_span = rows.max(1);
+        self.column_span = columns.max(1);
+        self
+    }
+
+    /// Overrides the horizontal alignment of the grid for this child
+    pub fn align_h(mut self, align_h: AlignH) -> Self {
+        self.align_h = Some(align_h);
+        self
+    }
+
+    /// Overrides the vertical alignment of the grid for this child
+    pub fn align_v(mut self, align_v: AlignV) -> Self {
+        self.align_v = Some(align_v);
+        self
+    }
+
+    fn rows(&self) -> std::ops::Range<usize> {
+        self.row..self.row + self.row_span
+    }
+
+    fn columns(&self) -> std::ops::Range<usize> {
+        self.column..self.column + self.column_span
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct GridState {
+    pub(crate) columns: Vec<Track>,
+    pub(crate) rows: Vec<Track>,
+    pub(crate) column_gap: f32,
+    pub(crate) row_gap: f32,
+}
+
+impl GridState {
+    /// Resolve the cells of the children, in the same order
+    pub(crate) fn place(&self, cells: &[Option<GridCell>]) -> Vec<GridCell> {
+        let column_count = self.columns.len().max(1);
+        let mut occupied = HashSet::new();
+
+        cells.iter().flatten().for_each(|cell| {
+            cell.rows().for_each(|row| {
+                cell.columns().for_each(|column| { occupied.insert((row, column)); })
+            })
+        });
+
+        let mut next = 0;
+        cells.iter()
+            .map(|cell| match cell {
+                Some(cell) => *cell,
+                None => {
+                    while occupied.contains(&(next / column_count, next % column_count)) {
+                        next += 1;
+                    }
+                    occupied.insert((next / column_count, next % column_count));
+                    GridCell::new(next / column_count, next % column_count)
+                }
+            })
+            .collect()
+    }
+
+    /// The size of the tracks & the gaps, without the padding
+    pub(crate) fn measure(&self, items: &[(GridCell, Size)]) -> Size {
+        let (columns, rows) = self.track_sizes(items, None);
+        Size::new(
+            total(&columns, self.column_gap),
+            total(&rows, self.row_gap),
+        )
+    }
+
+    /// The rect of each child inside the content box of the grid.
+    /// The tracks start at the top left corner, and each child is aligned inside its cell,
+    /// keeping its own size unless it's stretched
+    pub(crate) fn arrange(
+        &self,
+        items: &[(GridCell, Size)],
+        content: Rect,
+        align_h: AlignH,
+        align_v: AlignV,
+    ) -> Vec<Rect> {
+        let (columns, rows) = self.track_sizes(items, Some(content.size()));
+        let column_starts = starts(&columns, content.x, self.column_gap);
+        let row_starts = starts(&rows, content.y, self.row_gap);
+
+        items.iter()
+            .map(|(cell, size)| {
+                let width = total(&columns[cell.columns()], self.column_gap);
+                let height = total(&rows[cell.rows()], self.row_gap);
+                let (x, width) = match cell.align_h.unwrap_or(align_h) {
+                    AlignH::Left => (0.0, size.width),
+                    AlignH::Center => ((width - size.width) / 2.0, size.width),
+                    AlignH::Right => (width - size.width, size.width),
+                    AlignH::Stretch => (0.0, width),
+                };
+                let (y, height) = match cell.align_v.unwrap_or(align_v) {
+                    AlignV::Top => (0.0, size.height),
+                    AlignV::Middle => ((height - size.height) / 2.0, size.height),
+                    AlignV::Bottom => (height - size.height, size.height),
+                    AlignV::Stretch => (0.0, height),
+                };
+                Rect::new(column_starts[cell.column] + x, row_starts[cell.row] + y, width, height)
+            })
+            .collect()
+    }
+
+    /// The sizes of the columns & rows. The fractions only grow into the available space while arranging
+    fn track_sizes(&self, items: &[(GridCell, Size)], available: Option<Size>) -> (Vec<f32>, Vec<f32>) {
+        let column_count = items.iter()
+            .map(|(cell, _)| cell.column + cell.column_span)
+            .fold(self.columns.len().max(1), usize::max);
+        let row_count = items.iter()
+            .map(|(cell, _)| cell.row + cell.row_span)
+            .fold(self.rows.len(), usize::max);
+
+        let columns = items.iter()
+            .map(|(cell, size)| (cell.column, cell.column_span, size.width))
+            .collect::<Vec<_>>();
+        let rows = items.iter()
+            .map(|(cell, size)| (cell.row, cell.row_span, size.height))
+            .collect::<Vec<_>>();
+
+        (
+            size_tracks(&self.columns, column_count, self.column_gap, &columns, available.map(|s| s.width)),
+            size_tracks(&self.rows, row_count, self.row_gap, &rows, available.map(|s| s.height)),
+        )
+    }
+}
+
+/// The sizes of the tracks along one axis, the tracks past the defined ones are [`Track::Auto`].
+/// Each item is the first track, the span & the size of a child along the axis.
+fn size_tracks(
+    defined: &[Track],
+    count: usize,
+    gap: f32,
+    items: &[(usize, usize, f32)],
+    available: Option<f32>,
+) -> Vec<f32> {
+    let tracks = (0..count)
+        .map(|i| defined.get(i).copied().unwrap_or(Track::Auto))
+        .collect::<Vec<_>>();
+    let mut sizes = tracks.iter()
+        .map(|track| match track {
+            Track::Fixed(value) => *value,
+            Track::Auto | Track::Fraction(_) => 0.0,
+        })
+        .collect::<Vec<_>>();
+    // the size of a single fraction
+    let mut unit = 0.0f32;
+
+    // the children inside a single track first, then the spanning ones only grow what is missing
+    let (single, spanning): (Vec<_>, Vec<_>) = items.iter().partition(|(_, span, _)| *span == 1);
+
+    single.iter().for_each(|(i, _, content)| match tracks[*i] {
+        Track::Auto => sizes[*i] = sizes[*i].max(*content),
+        Track::Fraction(fr) if fr > 0.0 => unit = unit.max(content / fr),
+        _ => {},
+    });
+
+    spanning.iter().for_each(|(first, span, content)| {
+        let range = *first..*first + *span;
+        let fr_sum = tracks[range.clone()].iter().map(fraction).sum::<f32>();
+        let covered = total(&sizes[range.clone()], gap) + unit * fr_sum;
+        let missing = content - covered;
+        if missing <= 0.0 { return }
+
+        let autos = range.clone().filter(|i| tracks[*i] == Track::Auto).collect::<Vec<_>>();
+        if !autos.is_empty() {
+            autos.iter().for_each(|i| sizes[*i] += missing / autos.len() as f32);
+        } else if fr_sum > 0.0 {
+            unit += missing / fr_sum;
+        }
+    });
+
+    let fr_sum = tracks.iter().map(fraction).sum::<f32>();
+    if let Some(available) = available
+    && fr_sum > 0.0
+    {
+        let left = available - total(&sizes, gap);
+        unit = unit.max(left / fr_sum);
+    }
+
+    tracks.iter()
+        .zip(sizes.iter_mut())
+        .for_each(|(track, size)| *size += fraction(track) * unit);
+
+    sizes
+}
+
+fn fraction(track: &Track) -> f32 {
+    match track {
+        Track::Fraction(fr) => fr.max(0.0),
+        _ => 0.0,
+    }
+}
+
+/// The length of the tracks, including the gaps between them
+fn total(sizes: &[f32], gap: f32) -> f32 {
+    sizes.iter().sum::<f32>() + gap * sizes.len().saturating_sub(1) as f32
+}
+
+fn starts(sizes: &[f32], origin: f32, gap: f32) -> Vec<f32> {
+    sizes.iter()
+        .scan(origin, |next, size| {
+            let start = *next;
+            *next += size + gap;
+            Some(start)
+        })
+        .collect()
+}
+
+/// Lays out its children on rows & columns of [`Track`]s.
+/// The horizontal & vertical alignment of the grid align the children inside their cell
+pub struct Grid {
+    node: ViewNode,
+}
+
+impl Default for Grid {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Grid {
+    pub fn new() -> Self {
+        let node = ViewNode::new()
+            .with_name("Grid")
+            .with_size((1, 1))
+            .with_background_paint(Rgba::TRANSPARENT)
+            .with_border_paint(Rgba::TRANSPARENT)
+            .with_shape(Shape::Rect);
+
+        Self { node }.set_state(|state| state.grid = Some(GridState::default()))
+    }
+
+    /// Without any column, the grid has a single [`Track::Auto`] one
+    pub fn columns(self, columns: impl IntoIterator<Item = Track>) -> Self {
+        let columns = columns.into_iter().collect::<Vec<_>>();
+        self.set_grid(move |grid| grid.columns = columns.clone())
+    }
+
+    /// The rows needed past the defined ones are [`Track::Auto`]
+    pub fn rows(self, rows: impl IntoIterator<Item = Track>) -> Self {
+        let rows = rows.into_iter().collect::<Vec<_>>();
+        self.set_grid(move |grid| grid.rows = rows.clone())
+    }
+
+    /// The space between the rows & between the columns
+    pub fn gap(self, value: f32) -> Self {
+        self.set_grid(|grid| {
+            grid.row_gap = value;
+            grid.column_gap = value;
+        })
+    }
+
+    pub fn row_gap(self, value: f32) -> Self {
+        self.set_grid(|grid| grid.row_gap = value)
+    }
+
+    pub fn column_gap(self, value: f32) -> Self {
+        self.set_grid(|grid| grid.column_gap = value)
+    }
+
+    fn set_grid(self, mut f: impl FnMut(&mut GridState)) -> Self {
+        self.set_state(|state| {
+            if let Some(grid) = state.grid.as_mut() {
+                f(grid);
+            }
+        })
+    }
+}
+
+impl Widget for Grid {
+    fn node(&self) -> ViewNode {
+        self.node
+    }
+}
+
+#[cfg(test)]
+mod grid_test {
+    use super::*;
+    use crate::context::layout::{LayoutContext, Padding, calculate_size_recursive};
+    use crate::view::VIEW_STORAGE;
+    use crate::widget::Button;
+    use aplite_types::Vec2f;
+
+    #[test]
+    fn track_sizing_and_placement() {
+        let grid = GridState {
+            columns: vec![Track::Fixed(50.0), Track::Auto, Track::Fraction(1.0), Track::Fraction(2.0)],
+            rows: vec![Track::Auto],
+            column_gap: 10.0,
+            row_gap: 5.0,
+        };
+
+        let cells = grid.place(&[
+            None,
+            Some(GridCell::new(0, 1)),
+            None,
+            Some(GridCell::new(1, 0).span(1, 2)),
+            None,
+        ]);
+        assert_eq!(
+            cells.iter().map(|c| (c.row, c.column)).collect::<Vec<_>>(),
+            vec![(0, 0), (0, 1), (0, 2), (1, 0), (0, 3)],
+        );
+
+        let sizes = [(20., 10.), (30., 20.), (12., 10.), (120., 15.), (10., 40.)];
+        let items = cells.into_iter()
+            .zip(sizes.map(Size::from))
+            .collect::<Vec<_>>();
+
+        // the spanning child grows the auto column, the fractions fit their children
+        let measured = grid.measure(&items);
+        assert_eq!(measured, Size::new(50. + 60. + 12. + 24. + 30., 40. + 15. + 5.));
+
+        // the 90px left are shared by the fractions, so the columns are 50, 60, 30 & 60
+        let content = Rect::new(0., 0., 230., 100.);
+        let rects = grid.arrange(&items, content, AlignH::Center, AlignV::Bottom);
+        assert_eq!(rects[0], Rect::new(15., 30., 20., 10.));
+        assert_eq!(rects[2], Rect::new(139., 30., 12., 10.));
+        assert_eq!(rects[3], Rect::new(0., 45., 120., 15.));
+        assert_eq!(rects[4], Rect::new(195., 0., 10., 40.));
+
+        let rects = grid.arrange(&items, content, AlignH::Stretch, AlignV::Stretch);
+        assert_eq!(rects[2], Rect::new(130., 0., 30., 40.));
+    }
+
+    #[test]
+    fn grid_lays_out_its_children() {
+        let first = Button::new().size((40, 20));
+        let first_id = first.id();
+        let wide = Button::new().size((100, 30)).grid_cell(GridCell::new(1, 0).span(1, 2));
+        let wide_id = wide.id();
+        let last = Button::new().size((20, 20)).grid_cell(GridCell::new(0, 1).align_h(AlignH::Right));
+        let last_id = last.id();
+
+        let grid = Grid::new()
+            .columns([Track::Fixed(50.), Track::Fraction(1.)])
+            .gap(10.)
+            .set_state(|state| state.set_padding(Padding::splat(5.)))
+            .child(first)
+            .child(wide)
+            .child(last);
+        let grid_id = grid.id();
+
+        // the fraction column fits the part of the wide child past the fixed one
+        let size = calculate_size_recursive(&grid_id);
+        assert_eq!(size, Size::new(110., 70.));
+
+        VIEW_STORAGE.with(|s| s.tree.borrow_mut().get_mut(&grid_id).unwrap().set_size((210., 70.)));
+        LayoutContext::new(grid_id).calculate();
+
+        let pos = |id| VIEW_STORAGE.with(|s| s.tree.borrow().get(&id).unwrap().rect.vec2f());
+        assert_eq!(pos(first_id), Vec2f::new(5., 5.));
+        assert_eq!(pos(wide_id), Vec2f::new(5., 35.));
+        assert_eq!(pos(last_id), Vec2f::new(185., 5.));
+    }
+}
@@ -45,6 +45,6 @@ impl Image {
 
 impl Widget for Image {
     fn node(&self) -> ViewNode {
-        self.node.clone()
+        self.node
     }
 }
@@ -0,0 +1,284 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+use std::sync::{Arc, Mutex};
+
+use aplite_reactive::*;
+use aplite_renderer::Shape;
+use aplite_types::Rgba;
+
+use crate::view::{IntoView, View, ViewId, VIEW_STORAGE};
+
+use super::{ViewNode, Widget, bind_state};
+
+thread_local! {
+    /// Views whose children are rebuilt on the main thread, after an effect has sent the new input
+    pub(crate) static RECONCILERS: RefCell<HashMap<ViewId, Box<dyn FnMut() -> bool>>> = RefCell::new(HashMap::new());
+}
+
+pub fn for_each<T, K, KF, VF, IV>(each: Signal<Vec<T>>, key: KF, view: VF) -> For
+where
+    T: Clone + Send + 'static,
+    K: Eq + Hash + 'static,
+    KF: Fn(&T) -> K + 'static,
+    VF: Fn(T) -> IV + 'static,
+    IV: IntoView + 'static,
+{
+    For::new(each, key, view)
+}
+
+/// Creates one child per item, and keeps them in sync with the [`Signal`].
+/// Items are matched by their key, so the views of the unchanged items are moved instead of recreated.
+/// The keys should be unique, but the items sharing a key are matched in order
+pub struct For {
+    node: ViewNode,
+}
+
+impl For {
+    pub fn new<T, K, KF, VF, IV>(each: Signal<Vec<T>>, key: KF, view: VF) -> Self
+    where
+        T: Clone + Send + 'static,
+        K: Eq + Hash + 'static,
+        KF: Fn(&T) -> K + 'static,
+        VF: Fn(T) -> IV + 'static,
+        IV: IntoView + 'static,
+    {
+        let node = ViewNode::new()
+            .with_name("For")
+            .with_background_paint(Rgba::TRANSPARENT)
+            .with_border_paint(Rgba::TRANSPARENT)
+            .with_shape(Shape::Rect);
+
+        let id = node.id();
+        let pending = Arc::new(Mutex::new(Some(each.get_untracked())));
+
+        // the effect runs on the executor, so the items are only stored here,
+        // and the views are created on the main thread on the next redraw
+        let sender = Arc::clone(&pending);
+        bind_state(id, move |_| *sender.lock().unwrap() = Some(each.get()), |_, _| {});
+
+        // the first items are needed for the first layout
+        let mut list = List {
+            id,
+            key,
+            view,
+            pending,
+            children: Vec::new(),
+        };
+        list.reconcile();
+
+        register_reconciler(id, move || list.reconcile());
+
+        Self { node }
+    }
+}
+
+impl Widget for For {
+    fn node(&self) -> ViewNode {
+        self.node
+    }
+}
+
+struct List<T, K, KF, VF> {
+    id: ViewId,
+    key: KF,
+    view: VF,
+    pending: Arc<Mutex<Option<Vec<T>>>>,
+    children: Vec<(K, ViewId)>,
+}
+
+impl<T, K, KF, VF, IV> List<T, K, KF, VF>
+where
+    K: Eq + Hash,
+    KF: Fn(&T) -> K,
+    VF: Fn(T) -> IV,
+    IV: IntoView,
+{
+    /// Diff the pending items with the current children by key.
+    /// Returns `true` if any child was inserted, moved, or removed
+    fn reconcile(&mut self) -> bool {
+        let Some(items) = self.pending.lock().unwrap().take() else { return false };
+
+        let prev_order = self.children.iter().map(|(_, id)| *id).collect::<Vec<_>>();
+        // the views sharing a key are reused in order, and the ones left over are removed
+        let mut prev = HashMap::<K, VecDeque<ViewId>>::new();
+        self.children
+            .drain(..)
+            .for_each(|(key, id)| prev.entry(key).or_default().push_back(id));
+        let mut created = Vec::new();
+
+        let children = items
+            .into_iter()
+            .map(|item| {
+                let key = (self.key)(&item);
+                let id = prev.get_mut(&key).and_then(VecDeque::pop_front).unwrap_or_else(|| {
+                    let view = (self.view)(item);
+                    let id = view.id();
+                    created.push(view.into_view());
+                    id
+                });
+                (key, id)
+            })
+            .collect::<Vec<_>>();
+
+        let changed = prev_order.len() != children.len()
+            || prev_order.iter().zip(&children).any(|(prev, (_, id))| prev != id);
+        self.children = children;
+
+        if !changed { return false }
+
+        VIEW_STORAGE.with(|s| {
+            s.remove_all(&prev.into_values().flatten().collect::<Vec<_>>());
+
+            let mut tree = s.tree.borrow_mut();
+            tree.get_all_children(&self.id)
+                .into_iter()
+                .flatten()
+                .for_each(|child| tree.detach(&child));
+            self.children
+                .iter()
+                .for_each(|(_, child)| tree.add_child(&self.id, *child));
+            drop(tree);
+
+            let mut storage = s.storage.borrow_mut();
+            created
+                .into_iter()
+                .for_each(|view: View| { storage.insert(view.node.id(), view); });
+        });
+
+        true
+    }
+}
+
+/// `reconcile` returns `true` if the children of the view have changed
+pub(super) fn register_reconciler(id: ViewId, reconcile: impl FnMut() -> bool + 'static) {
+    RECONCILERS.with(|r| r.borrow_mut().insert(id, Box::new(reconcile)));
+}
+
+/// Forget the reconcilers of the removed views
+pub(crate) fn remove_reconcilers(removed: &HashSet<ViewId>) {
+    RECONCILERS.with(|r| r.borrow_mut().retain(|id, _| !removed.contains(id)));
+}
+
+/// Rebuild the children of the updated views.
+/// Returns the ones whose children have changed, which need to be laid out again
+pub(crate) fn reconcile_children(updated: &[ViewId]) -> Vec<ViewId> {
+    let mut changed = Vec::new();
+
+    updated.iter().for_each(|id| {
+        // taken out, because the view function may create another reconciled view
+        let Some(mut reconcile) = RECONCILERS.with(|r| r.borrow_mut().remove(id)) else { return };
+
+        if reconcile() && !changed.contains(id) {
+            changed.push(*id);
+        }
+
+        if VIEW_STORAGE.with(|s| s.tree.borrow().contains(id)) {
+            RECONCILERS.with(|r| r.borrow_mut().insert(*id, reconcile));
+        }
+    });
+
+    changed
+}
+
+#[cfg(test)]
+mod list_test {
+    use aplite_future::Executor;
+    use crate::context::layout::{LayoutContext, calculate_size_recursive};
+    use crate::view::run_effects;
+    use crate::widget::{Button, VStack, WidgetExt};
+    use super::*;
+
+    fn children(id: &ViewId) -> Vec<ViewId> {
+        VIEW_STORAGE.with(|s| s.tree.borrow().get_all_children(id).unwrap_or_default())
+    }
+
+    #[test]
+    fn keyed_diff() {
+        Executor::init();
+
+        let items = Signal::new(vec![1u32, 2, 3]);
+        let list = for_each(items.clone(), |n| *n, |_| Button::new().size((10, 10)));
+        let id = list.id();
+
+        let first = children(&id);
+        assert_eq!(first.len(), 3);
+
+        // 2 is removed, and 1 is moved after 3
+        items.set(vec![3, 1, 4]);
+        run_effects();
+        let second = children(&id);
+        assert_eq!(&second[..2], &[first[2], first[0]]);
+        assert!(!first.contains(&second[2]));
+        VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            assert!(!tree.contains(&first[1]));
+            assert!(!s.hoverable.borrow().contains(&first[1]));
+
+            // the list is measured & laid out again
+            let ys = second.iter().map(|id| tree.get(id).unwrap().rect.y).collect::<Vec<_>>();
+            assert_eq!(ys, [0., 10., 20.]);
+            assert_eq!(tree.get(&id).unwrap().rect.height, 30.);
+        });
+
+        items.set(vec![]);
+        run_effects();
+        assert!(children(&id).is_empty());
+    }
+
+    #[test]
+    fn duplicate_keys() {
+        Executor::init();
+
+        let items = Signal::new(vec![1u32, 1, 2]);
+        let list = for_each(items.clone(), |n| *n, |_| Button::new().size((10, 10)));
+        let id = list.id();
+
+        let first = children(&id);
+        assert_eq!(first.len(), 3);
+
+        // the views sharing the key are matched in order, and none is leaked
+        items.set(vec![1]);
+        run_effects();
+        let second = children(&id);
+        assert_eq!(second, [first[0]]);
+        VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            assert!(!tree.contains(&first[1]));
+            assert!(!tree.contains(&first[2]));
+        });
+    }
+
+    #[test]
+    fn nested_list_is_laid_out_up_to_a_fixed_ancestor() {
+        Executor::init();
+
+        let items = Signal::new(vec![1u32]);
+        let list = for_each(items.clone(), |n| *n, |_| Button::new().size((10, 10)));
+        let sibling = Button::new().size((10, 10));
+        let sibling_id = sibling.id();
+        let inner = VStack::new().child(list).child(sibling);
+        let inner_id = inner.id();
+        let fixed = VStack::new().size((100, 100)).child(inner);
+        let marker = Button::new().size((10, 10));
+        let marker_id = marker.id();
+        let root = VStack::new().size((200, 200)).child(fixed).child(marker);
+        let root_id = root.id();
+
+        calculate_size_recursive(&root_id);
+        LayoutContext::new(root_id).calculate();
+        let rect_of = |id: &ViewId| VIEW_STORAGE.with(|s| s.tree.borrow().get(id).unwrap().rect);
+        assert_eq!(rect_of(&sibling_id).y, 10.);
+
+        // moved by hand, so it's only put back if the root is laid out again
+        VIEW_STORAGE.with(|s| s.tree.borrow_mut().get_mut(&marker_id).unwrap().rect.x = 50.);
+
+        items.set(vec![1, 2, 3]);
+        run_effects();
+
+        // the stack around the list grows, but the fixed one keeps its size, so the layout stops there
+        assert_eq!(rect_of(&inner_id).height, 40.);
+        assert_eq!(rect_of(&sibling_id).y, 30.);
+        assert_eq!(rect_of(&marker_id).x, 50.);
+    }
+}
@@ -0,0 +1,160 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+
+use crate::view::{IntoView, View, ViewId};
+
+use super::bind_state;
+
+thread_local! {
+    /// The popups, by the id of their anchor
+    static POPUPS: RefCell<HashMap<ViewId, PopupEntry>> = RefCell::new(HashMap::new());
+}
+
+type Factory = Box<dyn Fn() -> View>;
+
+/// Where the popup is placed, relative to its anchor.
+/// When there is not enough room, the popup is flipped to the opposite side,
+/// then shifted to stay inside the window
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Placement {
+    Top,
+    #[default]
+    Bottom,
+    Left,
+    Right,
+    /// The center of the window, ignoring the anchor
+    Center,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PopupOptions {
+    pub(crate) placement: Placement,
+    /// the distance from the anchor
+    pub(crate) gap: f32,
+    /// blocks the input of everything underneath
+    pub(crate) modal: bool,
+    pub(crate) dismiss_on_outside_click: bool,
+}
+
+/// A view mounted on the overlay layer of the window while the condition is `true`.
+/// It escapes the layout & the clip of its anchor, and is drawn above the main tree.
+/// Attach it to its anchor with [`WidgetExt::popup`](super::WidgetExt::popup)
+pub struct Popup {
+    when: Box<dyn Fn() -> bool>,
+    view: Factory,
+    options: PopupOptions,
+    on_dismiss: Option<Box<dyn FnMut()>>,
+}
+
+pub fn popup<W, VF, IV>(when: W, view: VF) -> Popup
+where
+    W: Fn() -> bool + 'static,
+    VF: Fn() -> IV + 'static,
+    IV: IntoView + 'static,
+{
+    Popup::new(when, view)
+}
+
+impl Popup {
+    pub fn new<W, VF, IV>(when: W, view: VF) -> Self
+    where
+        W: Fn() -> bool + 'static,
+        VF: Fn() -> IV + 'static,
+        IV: IntoView + 'static,
+    {
+        Self {
+            when: Box::new(when),
+            view: Box::new(move || view().into_view()),
+            options: PopupOptions {
+                placement: Placement::default(),
+                gap: 4.0,
+                modal: false,
+                dismiss_on_outside_click: true,
+            },
+            on_dismiss: None,
+        }
+    }
+
+    pub fn placement(mut self, placement: Placement) -> Self {
+        self.options.placement = placement;
+        self
+    }
+
+    /// The distance from the anchor, in logical pixels
+    pub fn gap(mut self, gap: f32) -> Self {
+        self.options.gap = gap;
+        self
+    }
+
+    /// A modal popup blocks the hover, click, scroll & focus of everything underneath
+    pub fn modal(mut self, modal: bool) -> Self {
+        self.options.modal = modal;
+        self
+    }
+
+    pub fn dismiss_on_outside_click(mut self, value: bool) -> Self {
+        self.options.dismiss_on_outside_click = value;
+        self
+    }
+
+    /// Called when the popup is closed by a click outside of it.
+    /// Use this to reset the condition, so the popup can be opened again
+    pub fn on_dismiss<F: FnMut() + 'static>(mut self, f: F) -> Self {
+        self.on_dismiss = Some(Box::new(f));
+        self
+    }
+}
+
+struct PopupEntry {
+    /// the last value of the condition, not yet applied
+    pending: Arc<Mutex<Option<bool>>>,
+    view: Factory,
+    options: PopupOptions,
+    on_dismiss: Option<Box<dyn FnMut()>>,
+}
+
+pub(super) fn register_popup(anchor: ViewId, popup: Popup) {
+    let Popup { when, view, options, on_dismiss } = popup;
+
+    // the first value is needed to open the popup on the first frame
+    let pending = Arc::new(Mutex::new(Some(when())));
+    let sender = Arc::clone(&pending);
+    bind_state(anchor, move |_| *sender.lock().unwrap() = Some(when()), |_, _| {});
+
+    let entry = PopupEntry { pending, view, options, on_dismiss };
+    POPUPS.with(|p| p.borrow_mut().insert(anchor, entry));
+}
+
+/// The anchors of the popups whose condition has changed, and whether they should be opened
+pub(crate) fn take_popup_requests() -> Vec<(ViewId, bool)> {
+    POPUPS.with(|p| {
+        p.borrow()
+            .iter()
+            .filter_map(|(anchor, entry)| entry.pending.lock().unwrap().take().map(|open| (*anchor, open)))
+            .collect()
+    })
+}
+
+/// Build a new view of the popup
+pub(crate) fn create_popup(anchor: &ViewId) -> Option<(View, PopupOptions)> {
+    // taken out, because the factory may register the popups of the new views
+    let entry = POPUPS.with(|p| p.borrow_mut().remove(anchor))?;
+    let view = (entry.view)();
+    let options = entry.options;
+    POPUPS.with(|p| p.borrow_mut().insert(*anchor, entry));
+    Some((view, options))
+}
+
+pub(crate) fn call_on_dismiss(anchor: &ViewId) {
+    let Some(mut entry) = POPUPS.with(|p| p.borrow_mut().remove(anchor)) else { return };
+    if let Some(f) = entry.on_dismiss.as_mut() {
+        f();
+    }
+    POPUPS.with(|p| p.borrow_mut().insert(*anchor, entry));
+}
+
+/// Forget the popups anchored to the removed views
+pub(crate) fn remove_popups(removed: &HashSet<ViewId>) {
+    POPUPS.with(|p| p.borrow_mut().retain(|id, _| !removed.contains(id)));
+}
@@ -0,0 +1,168 @@
+use aplite_reactive::*;
+use aplite_renderer::Shape;
+use aplite_types::{Rect, Rgba, Size, Vec2f};
+
+use crate::state::WidgetState;
+use crate::view::IntoView;
+
+use super::{ViewNode, Widget, WidgetExt, bind_state};
+
+pub(crate) const SCROLLBAR_COLOR: Rgba<u8> = Rgba::new(160, 160, 160, 180);
+const SCROLLBAR_WIDTH: f32 = 6.0;
+const SCROLLBAR_MARGIN: f32 = 2.0;
+const MIN_THUMB_LENGTH: f32 = 20.0;
+
+pub fn scroll_view(child: impl IntoView + 'static) -> ScrollView {
+    ScrollView::new(child)
+}
+
+#[derive(Clone)]
+pub(crate) struct ScrollState {
+    pub(crate) offset: Vec2f,
+    /// the natural size of the children, including the padding
+    pub(crate) content: Size,
+    pub(crate) scrollbar: bool,
+    pub(crate) signal: Option<Signal<Vec2f>>,
+}
+
+impl Default for ScrollState {
+    fn default() -> Self {
+        Self {
+            offset: Vec2f::default(),
+            content: Size::default(),
+            scrollbar: true,
+            signal: None,
+        }
+    }
+}
+
+impl ScrollState {
+    pub(crate) fn max_offset(&self, viewport: Size) -> Vec2f {
+        Vec2f::new(
+            (self.content.width - viewport.width).max(0.0),
+            (self.content.height - viewport.height).max(0.0),
+        )
+    }
+
+    pub(crate) fn clamp(&self, offset: Vec2f, viewport: Size) -> Vec2f {
+        let max = self.max_offset(viewport);
+        Vec2f::new(
+            offset.x.clamp(0.0, max.x),
+            offset.y.clamp(0.0, max.y),
+        )
+    }
+
+    /// The vertical & horizontal scrollbar thumbs, only if the content overflows on that axis
+    pub(crate) fn thumbs(&self, rect: Rect) -> [Option<Rect>; 2] {
+        let max = self.max_offset(rect.size());
+
+        let vertical = (max.y > 0.0).then(|| {
+            let (len, pos) = thumb(rect.height, self.content.height, self.offset.y, max.y);
+            Rect::new(rect.max_x() - SCROLLBAR_WIDTH - SCROLLBAR_MARGIN, rect.y + pos, SCROLLBAR_WIDTH, len)
+        });
+
+        let horizontal = (max.x > 0.0).then(|| {
+            let (len, pos) = thumb(rect.width, self.content.width, self.offset.x, max.x);
+            Rect::new(rect.x + pos, rect.max_y() - SCROLLBAR_WIDTH - SCROLLBAR_MARGIN, len, SCROLLBAR_WIDTH)
+        });
+
+        [vertical, horizontal]
+    }
+
+    /// How much the offset moves for each pixel the thumb is dragged along the track
+    pub(crate) fn drag_ratio(&self, rect: Rect, vertical: bool) -> f32 {
+        let max = self.max_offset(rect.size());
+        let (track, content, max) = if vertical {
+            (rect.height, self.content.height, max.y)
+        } else {
+            (rect.width, self.content.width, max.x)
+        };
+        let (len, _) = thumb(track, content, 0.0, max);
+        let travel = track - len;
+
+        if travel > 0.0 { max / travel } else { 0.0 }
+    }
+}
+
+/// The length & the position of a thumb along its track
+fn thumb(track: f32, content: f32, offset: f32, max: f32) -> (f32, f32) {
+    let len = (track * track / content).clamp(MIN_THUMB_LENGTH.min(track), track);
+    let pos = if max > 0.0 { offset / max * (track - len) } else { 0.0 };
+    (len, pos)
+}
+
+/// Lays out its child at the natural size, and only shows the part inside its own rect
+pub struct ScrollView {
+    node: ViewNode,
+}
+
+impl ScrollView {
+    pub fn new(child: impl IntoView + 'static) -> Self {
+        let node = ViewNode::new()
+            .with_name("ScrollView")
+            .with_size((300, 300))
+            .with_background_paint(Rgba::TRANSPARENT)
+            .with_border_paint(Rgba::TRANSPARENT)
+            .with_shape(Shape::Rect);
+
+        Self { node }
+            .set_state(|state| state.scroll = Some(ScrollState::default()))
+            .child(child)
+    }
+
+    /// Read & write the scroll offset through the [`Signal`]
+    pub fn offset(self, signal: Signal<Vec2f>) -> Self {
+        let offset = signal.get_untracked();
+        bind_state(self.id(), move |_| signal.get(), WidgetState::set_scroll_offset);
+
+        self.set_state(|state| {
+            if let Some(scroll) = state.scroll.as_mut() {
+                scroll.signal = Some(signal);
+            }
+            state.set_scroll_offset(offset);
+        })
+    }
+
+    /// Show the draggable scrollbars, enabled by default
+    pub fn scrollbar(self, value: bool) -> Self {
+        self.set_state(|state| {
+            if let Some(scroll) = state.scroll.as_mut() {
+                scroll.scrollbar = value;
+            }
+        })
+    }
+}
+
+impl Widget for ScrollView {
+    fn node(&self) -> ViewNode {
+        self.node
+    }
+}
+
+#[cfg(test)]
+mod scroll_test {
+    use super::*;
+
+    #[test]
+    fn thumb_geometry() {
+        let scroll = ScrollState {
+            content: Size::new(100.0, 400.0),
+            offset: Vec2f::new(0.0, 300.0),
+            ..Default::default()
+        };
+        let rect = Rect::new(0.0, 0.0, 100.0, 100.0);
+
+        assert_eq!(scroll.max_offset(rect.size()), Vec2f::new(0.0, 300.0));
+        assert_eq!(scroll.clamp(Vec2f::new(-5.0, 500.0), rect.size()), Vec2f::new(0.0, 300.0));
+
+        // no horizontal overflow, the vertical thumb sits at the end of the track
+        let [vertical, horizontal] = scroll.thumbs(rect);
+        let vertical = vertical.unwrap();
+        assert!(horizontal.is_none());
+        assert_eq!(vertical.height, 25.0);
+        assert_eq!(vertical.max_y(), 100.0);
+
+        // dragging the thumb along the 75px of travel covers the 300px of offset
+        assert_eq!(scroll.drag_ratio(rect, true), 4.0);
+    }
+}
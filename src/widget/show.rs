@@ -0,0 +1,325 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::sync::{Arc, Mutex};
+
+use aplite_renderer::Shape;
+use aplite_types::Rgba;
+
+use crate::view::{IntoView, View, ViewId, VIEW_STORAGE};
+
+use super::{ViewNode, Widget, bind_state_eager};
+use super::list::register_reconciler;
+
+type Factory = Box<dyn Fn() -> View>;
+
+pub fn show<W, VF, IV>(when: W, view: VF) -> Show
+where
+    W: Fn() -> bool + 'static,
+    VF: Fn() -> IV + 'static,
+    IV: IntoView + 'static,
+{
+    Show::new(when, view)
+}
+
+pub fn switch<K, F>(select: F) -> Switch<K>
+where
+    K: PartialEq + Clone + Send + 'static,
+    F: Fn() -> K + 'static,
+{
+    Switch::new(select)
+}
+
+/// Mounts the view while the condition is `true`, and the fallback, if any, otherwise.
+/// The unmounted subtree is removed, along with its callbacks and effects.
+pub struct Show {
+    inner: Switch<bool>,
+}
+
+impl Show {
+    pub fn new<W, VF, IV>(when: W, view: VF) -> Self
+    where
+        W: Fn() -> bool + 'static,
+        VF: Fn() -> IV + 'static,
+        IV: IntoView + 'static,
+    {
+        let inner = Switch::new(when)
+            .with_name("Show")
+            .case(true, view);
+
+        Self { inner }
+    }
+
+    pub fn fallback<VF, IV>(self, view: VF) -> Self
+    where
+        VF: Fn() -> IV + 'static,
+        IV: IntoView + 'static,
+    {
+        Self { inner: self.inner.fallback(view) }
+    }
+}
+
+impl Widget for Show {
+    fn node(&self) -> ViewNode {
+        self.inner.node
+    }
+}
+
+/// Mounts the view of the first case matching the selected key, or the fallback, if any.
+/// The view is only recreated when another case is selected.
+pub struct Switch<K> {
+    node: ViewNode,
+    branch: Rc<RefCell<Branch<K>>>,
+}
+
+impl<K: PartialEq + Clone + Send + 'static> Switch<K> {
+    pub fn new<F>(select: F) -> Self
+    where
+        F: Fn() -> K + 'static,
+    {
+        let node = ViewNode::new()
+            .with_name("Switch")
+            .with_background_paint(Rgba::TRANSPARENT)
+            .with_border_paint(Rgba::TRANSPARENT)
+            .with_shape(Shape::Rect);
+
+        let id = node.id();
+        let pending = Arc::new(Mutex::new(None));
+
+        // the first key is selected right away for the first layout,
+        // and the next ones are stored for the reconciler on the main thread
+        let sender = Arc::clone(&pending);
+        let key = bind_state_eager(id, move |prev: Option<K>| {
+            let key = select();
+            if prev.is_some() {
+                *sender.lock().unwrap() = Some(key.clone());
+            }
+            key
+        }, |_, _| {});
+
+        let branch = Rc::new(RefCell::new(Branch {
+            id,
+            pending,
+            key,
+            cases: Vec::new(),
+            fallback: None,
+            mounted: None,
+        }));
+
+        let reconciled = Rc::clone(&branch);
+        register_reconciler(id, move || reconciled.borrow_mut().reconcile());
+
+        Self { node, branch }
+    }
+
+    fn with_name(self, name: &'static str) -> Self {
+        Self { node: self.node.with_name(name), branch: self.branch }
+    }
+
+    pub fn case<VF, IV>(self, key: K, view: VF) -> Self
+    where
+        VF: Fn() -> IV + 'static,
+        IV: IntoView + 'static,
+    {
+        let mut branch = self.branch.borrow_mut();
+        branch.cases.push((key, Box::new(move || view().into_view())));
+        branch.mount();
+        drop(branch);
+        self
+    }
+
+    /// Mounted when none of the cases matches
+    pub fn fallback<VF, IV>(self, view: VF) -> Self
+    where
+        VF: Fn() -> IV + 'static,
+        IV: IntoView + 'static,
+    {
+        let mut branch = self.branch.borrow_mut();
+        branch.fallback = Some(Box::new(move || view().into_view()));
+        branch.mount();
+        drop(branch);
+        self
+    }
+}
+
+impl<K> Widget for Switch<K> {
+    fn node(&self) -> ViewNode {
+        self.node
+    }
+}
+
+/// Which view is mounted: the index of the case, or [`None`] for the fallback
+type Slot = Option<usize>;
+
+struct Branch<K> {
+    id: ViewId,
+    pending: Arc<Mutex<Option<K>>>,
+    key: K,
+    cases: Vec<(K, Factory)>,
+    fallback: Option<Factory>,
+    mounted: Option<(Slot, Option<ViewId>)>,
+}
+
+impl<K: PartialEq> Branch<K> {
+    /// Returns `true` if the mounted view has changed
+    fn reconcile(&mut self) -> bool {
+        let Some(key) = self.pending.lock().unwrap().take() else { return false };
+        self.key = key;
+        self.mount()
+    }
+
+    fn mount(&mut self) -> bool {
+        let slot = self.cases.iter().position(|(key, _)| *key == self.key);
+        let factory = match slot {
+            Some(index) => Some(&self.cases[index].1),
+            None => self.fallback.as_ref(),
+        };
+
+        let mounted = self.mounted.as_ref().map(|(slot, id)| (*slot, id.is_some()));
+        if mounted == Some((slot, factory.is_some())) { return false }
+
+        let view = factory.map(|f| f());
+        VIEW_STORAGE.with(|s| {
+            if let Some((_, Some(prev))) = self.mounted {
+                s.remove(&prev);
+            }
+            if let Some(view) = view.as_ref() {
+                s.tree.borrow_mut().add_child(&self.id, view.node.id());
+            }
+        });
+
+        let id = view.map(|view| {
+            let id = view.node.id();
+            VIEW_STORAGE.with(|s| s.storage.borrow_mut().insert(id, view));
+            id
+        });
+        self.mounted = Some((slot, id));
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod show_test {
+    use aplite_future::Executor;
+    use aplite_reactive::*;
+    use crate::context::layout::{LayoutContext, calculate_size_recursive};
+    use crate::view::run_effects;
+    use crate::widget::{Button, CALLBACKS, VStack, WidgetEvent, WidgetExt};
+    use super::*;
+
+    fn children(id: &ViewId) -> Vec<ViewId> {
+        VIEW_STORAGE.with(|s| s.tree.borrow().get_all_children(id).unwrap_or_default())
+    }
+
+    #[test]
+    fn show_releases_unmounted_subtree() {
+        Executor::init();
+
+        let visible = Signal::new(true);
+        let id = show(move || visible.get(), || {
+            Button::new()
+                .color(|_| Rgba::RED)
+                .on(WidgetEvent::LeftClick, || {})
+        })
+        .fallback(Button::new)
+        .id();
+
+        let first = children(&id);
+        assert_eq!(first.len(), 1);
+
+        visible.set(false);
+        run_effects();
+        let second = children(&id);
+        assert_eq!(second.len(), 1);
+
+        VIEW_STORAGE.with(|s| {
+            assert!(!s.tree.borrow().contains(&first[0]));
+            assert!(!s.hoverable.borrow().contains(&first[0]));
+            assert!(!s.effects.borrow().contains_key(&first[0]));
+        });
+        assert!(CALLBACKS.with(|cb| !cb.borrow().contains_key(&first[0])));
+
+        visible.set(true);
+        run_effects();
+        let third = children(&id);
+        assert_eq!(third.len(), 1);
+        assert!(VIEW_STORAGE.with(|s| !s.tree.borrow().contains(&second[0])));
+    }
+
+    #[test]
+    fn siblings_follow_the_branch_size() {
+        Executor::init();
+
+        let expanded = Signal::new(false);
+        let branch = show(move || expanded.get(), || Button::new().size((10, 40)))
+            .fallback(|| Button::new().size((10, 10)));
+        let sibling = Button::new().size((10, 10));
+        let sibling_id = sibling.id();
+        let stack = VStack::new().child(branch).child(sibling);
+        let stack_id = stack.id();
+
+        calculate_size_recursive(&stack_id);
+        LayoutContext::new(stack_id).calculate();
+        let y_of = |id: &ViewId| VIEW_STORAGE.with(|s| s.tree.borrow().get(id).unwrap().rect.y);
+        assert_eq!(y_of(&sibling_id), 10.);
+
+        expanded.set(true);
+        run_effects();
+
+        // the stack grows with the branch, so it's laid out again
+        assert_eq!(y_of(&sibling_id), 40.);
+        assert_eq!(VIEW_STORAGE.with(|s| s.tree.borrow().get(&stack_id).unwrap().rect.height), 50.);
+    }
+
+    #[test]
+    fn switch_mounts_matching_case() {
+        Executor::init();
+
+        let page = Signal::new(0u8);
+        let id = switch(move || page.get())
+            .case(0, Button::new)
+            .case(1, Button::new)
+            .id();
+
+        let first = children(&id);
+        assert_eq!(first.len(), 1);
+
+        // no fallback, so nothing is mounted
+        page.set(2);
+        run_effects();
+        assert!(children(&id).is_empty());
+
+        page.set(1);
+        run_effects();
+        assert_eq!(children(&id).len(), 1);
+    }
+
+    #[test]
+    fn switch_selects_once_per_change() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        Executor::init();
+
+        let page = Signal::new(0u8);
+        let runs = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&runs);
+        let id = switch(move || {
+            counter.fetch_add(1, Ordering::Relaxed);
+            page.get()
+        })
+        .case(0, Button::new)
+        .case(1, Button::new)
+        .id();
+
+        // the first key is selected once, and tracked
+        let first = children(&id);
+        run_effects();
+        assert_eq!(runs.load(Ordering::Relaxed), 1);
+        assert_eq!(children(&id), first);
+
+        page.set(1);
+        run_effects();
+        assert_eq!(runs.load(Ordering::Relaxed), 2);
+        assert_ne!(children(&id), first);
+    }
+}
@@ -13,6 +13,12 @@ pub struct VStack {
     node: ViewNode,
 }
 
+impl Default for VStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl VStack {
     pub fn new() -> Self {
         let node = ViewNode::new()
@@ -30,7 +36,7 @@ impl VStack {
 
 impl Widget for VStack {
     fn node(&self) -> ViewNode {
-        self.node.clone()
+        self.node
     }
 }
 
@@ -42,6 +48,12 @@ pub struct HStack {
     node: ViewNode,
 }
 
+impl Default for HStack {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl HStack {
     pub fn new() -> Self {
         let node = ViewNode::new()
@@ -60,6 +72,6 @@ impl HStack {
 
 impl Widget for HStack {
     fn node(&self) -> ViewNode {
-        self.node.clone()
+        self.node
     }
 }
@@ -0,0 +1,185 @@
+use std::cell::RefCell;
+
+use aplite_renderer::{Font, GlyphQuad};
+use aplite_types::{Rect, Rgba, Size};
+
+use crate::state::WidgetState;
+use super::{ViewNode, Widget, WidgetExt, FnEl, bind_state, bind_state_eager};
+
+thread_local! {
+    static DEFAULT_FONT: RefCell<Option<Font>> = const { RefCell::new(None) };
+}
+
+/// Set the [`Font`] used by every [`Text`] without its own font
+pub(crate) fn set_default_font(font: Font) {
+    DEFAULT_FONT.with(|f| *f.borrow_mut() = Some(font));
+}
+
+pub fn text<F: FnEl<String> + 'static>(content_fn: F) -> Text {
+    Text::new(content_fn)
+}
+
+pub(crate) const SELECTION_COLOR: Rgba<u8> = Rgba::new(80, 140, 255, 120);
+
+const CARET_WIDTH: f32 = 1.5;
+
+#[derive(Clone)]
+pub(crate) struct TextState {
+    pub(crate) content: String,
+    pub(crate) font: Option<Font>,
+    pub(crate) font_size: f32,
+    pub(crate) color: Rgba<u8>,
+    /// resize the widget to wrap the content
+    pub(crate) fit_content: bool,
+    /// only editable text has a selection
+    pub(crate) selection: Option<Selection>,
+    /// the horizontal offset of the content, so the caret stays visible
+    pub(crate) scroll: f32,
+}
+
+impl Default for TextState {
+    fn default() -> Self {
+        Self {
+            content: String::new(),
+            font: None,
+            font_size: 16.0,
+            color: Rgba::WHITE,
+            fit_content: true,
+            selection: None,
+            scroll: 0.0,
+        }
+    }
+}
+
+/// Byte offsets into the content. The caret is the moving end, and the anchor is the fixed one
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Selection {
+    pub(crate) anchor: usize,
+    pub(crate) caret: usize,
+}
+
+impl Selection {
+    pub(crate) const fn collapsed(pos: usize) -> Self {
+        Self { anchor: pos, caret: pos }
+    }
+
+    pub(crate) fn start(&self) -> usize {
+        self.anchor.min(self.caret)
+    }
+
+    pub(crate) fn end(&self) -> usize {
+        self.anchor.max(self.caret)
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.anchor == self.caret
+    }
+
+    /// Keep both ends inside `content`, and on a char boundary
+    pub(crate) fn clamp(&self, content: &str) -> Self {
+        let clamp = |mut pos: usize| {
+            pos = pos.min(content.len());
+            while !content.is_char_boundary(pos) { pos -= 1 }
+            pos
+        };
+
+        Self {
+            anchor: clamp(self.anchor),
+            caret: clamp(self.caret),
+        }
+    }
+}
+
+impl TextState {
+    fn font(&self) -> Option<Font> {
+        self.font
+            .clone()
+            .or_else(|| DEFAULT_FONT.with(|f| f.borrow().clone()))
+    }
+
+    pub(crate) fn measure(&self) -> Size {
+        self.font()
+            .map(|font| font.measure(&self.content, self.font_size))
+            .unwrap_or_default()
+    }
+
+    pub(crate) fn layout(&self) -> Vec<GlyphQuad> {
+        self.font()
+            .map(|font| font.layout(&self.content, self.font_size))
+            .unwrap_or_default()
+    }
+
+    /// Scroll the least to bring the caret inside the visible width,
+    /// without leaving an empty space after the end of the content
+    pub(crate) fn scroll_to_caret(&mut self, visible: f32) {
+        let Some(font) = self.font() else { return };
+        let Some(selection) = self.selection.map(|selection| selection.clamp(&self.content)) else { return };
+        let x_of = |pos: usize| font.measure(&self.content[..pos], self.font_size).width;
+
+        let caret = x_of(selection.caret);
+        if caret < self.scroll {
+            self.scroll = caret;
+        } else if caret + CARET_WIDTH > self.scroll + visible {
+            self.scroll = caret + CARET_WIDTH - visible;
+        }
+
+        let max = x_of(self.content.len()) + CARET_WIDTH - visible;
+        self.scroll = self.scroll.min(max).max(0.0);
+    }
+
+    /// The selection highlight and the caret, relative to the top left corner of the text
+    pub(crate) fn selection_rects(&self) -> Option<(Rect, Rect)> {
+        let font = self.font()?;
+        let selection = self.selection?.clamp(&self.content);
+        let x_of = |pos: usize| font.measure(&self.content[..pos], self.font_size).width;
+        let height = font.line_height(self.font_size);
+
+        let start = x_of(selection.start());
+        let end = x_of(selection.end());
+        let caret = x_of(selection.caret);
+
+        Some((
+            Rect::new(start, 0.0, end - start, height),
+            Rect::new(caret, 0.0, CARET_WIDTH, height),
+        ))
+    }
+}
+
+pub struct Text {
+    node: ViewNode,
+}
+
+impl Text {
+    pub fn new<F: FnEl<String> + 'static>(content_fn: F) -> Self {
+        let node = ViewNode::new()
+            .with_name("Text")
+            .with_background_paint(Rgba::TRANSPARENT)
+            .with_border_paint(Rgba::TRANSPARENT);
+
+        // the effect runs on the executor, but the content is needed for the first layout
+        let content = bind_state_eager(node.id(), content_fn, WidgetState::set_text);
+
+        Self { node }.set_state(|state| state.set_text(content.clone()))
+    }
+
+    pub fn font_size<F: FnEl<f32> + 'static>(self, f: F) -> Self {
+        let font_size = bind_state_eager(self.id(), f, WidgetState::set_font_size);
+        self.set_state(|state| state.set_font_size(font_size))
+    }
+
+    pub fn text_color<F: FnEl<Rgba<u8>> + 'static>(self, f: F) -> Self {
+        bind_state(self.id(), f, WidgetState::set_text_color);
+        self
+    }
+
+    /// Use this [`Font`] instead of the default one set via [`Aplite::set_default_font`](crate::app::Aplite::set_default_font)
+    pub fn font(self, font: Font) -> Self {
+        self.set_state(|state| state.set_font(font.clone()))
+    }
+}
+
+impl Widget for Text {
+    fn node(&self) -> ViewNode {
+        self.node
+    }
+}
@@ -0,0 +1,356 @@
+use aplite_reactive::*;
+use aplite_renderer::Shape;
+use aplite_types::{Rgba, CornerRadius};
+use winit::keyboard::{Key, NamedKey};
+
+use crate::context::Context;
+use crate::context::cursor::CursorIcon;
+use crate::context::keyboard::KeyEvent;
+use crate::context::layout::Padding;
+use crate::state::WidgetState;
+use crate::view::{ViewId, VIEW_STORAGE};
+
+use super::{ViewNode, Widget, WidgetExt, WidgetEvent, FnEl, bind_state, bind_state_eager};
+use super::text::{TextState, Selection};
+
+pub fn text_input(value: Signal<String>) -> TextInput {
+    TextInput::new(value)
+}
+
+/// Single line editable text, kept in sync with the [`Signal`] in both directions
+pub struct TextInput {
+    node: ViewNode,
+}
+
+impl TextInput {
+    pub fn new(value: Signal<String>) -> Self {
+        let node = ViewNode::new()
+            .with_name("TextInput")
+            .with_size((200, 30))
+            .with_shape(Shape::RoundedRect)
+            .with_corner_radius(CornerRadius::splat(10.0))
+            .with_background_paint(Rgba::new(40, 40, 40, 255))
+            .with_border_paint(Rgba::new(120, 120, 120, 255))
+            .with_cursor_icon(CursorIcon::Text)
+            .set_hoverable();
+
+        let id = node.id();
+        let content = value.get_untracked();
+        let signal = value.clone();
+        bind_state(id, move |_| signal.get(), WidgetState::set_text);
+
+        Self { node }
+            .set_state(|state| {
+                state.set_padding(Padding::new(6.0, 6.0, 8.0, 8.0));
+                state.text = Some(TextState {
+                    content: content.clone(),
+                    fit_content: false,
+                    selection: Some(Selection::collapsed(content.len())),
+                    ..Default::default()
+                });
+                state.fit_text();
+            })
+            .on(WidgetEvent::Input, move |event: &KeyEvent| handle_key(id, &value, event))
+    }
+
+    pub fn font_size<F: FnEl<f32> + 'static>(self, f: F) -> Self {
+        let font_size = bind_state_eager(self.id(), f, WidgetState::set_font_size);
+        self.set_state(|state| state.set_font_size(font_size))
+    }
+
+    pub fn text_color<F: FnEl<Rgba<u8>> + 'static>(self, f: F) -> Self {
+        bind_state(self.id(), f, WidgetState::set_text_color);
+        self
+    }
+}
+
+impl Widget for TextInput {
+    fn node(&self) -> ViewNode {
+        self.node
+    }
+}
+
+/// The [`Signal`] is the source of truth for the content, the state only holds a copy to be rendered
+fn handle_key(id: ViewId, value: &Signal<String>, event: &KeyEvent) {
+    if !event.pressed { return }
+
+    let mut content = value.get_untracked();
+
+    let changed = VIEW_STORAGE.with(|s| {
+        let mut tree = s.tree.borrow_mut();
+        let Some(text) = tree.get_mut(&id).and_then(|state| state.text.as_mut()) else { return false };
+        let Some(selection) = text.selection.as_mut() else { return false };
+
+        *selection = selection.clamp(&content);
+        let changed = edit(&mut content, selection, event);
+        if changed {
+            text.content = content.clone();
+        }
+        tree.get_mut(&id).unwrap().fit_text();
+        changed
+    });
+
+    if changed {
+        value.set(content);
+    }
+
+    Context::toggle_dirty();
+}
+
+/// Apply the key to the content and the selection. Returns `true` if the content was modified
+fn edit(content: &mut String, selection: &mut Selection, event: &KeyEvent) -> bool {
+    let extend = event.modifiers.shift_key();
+    let by_word = event.modifiers.control_key() || event.modifiers.alt_key();
+
+    let move_to = |selection: &mut Selection, pos: usize| {
+        selection.caret = pos;
+        if !extend { selection.anchor = pos }
+    };
+
+    match &event.key {
+        Key::Named(NamedKey::ArrowLeft) => {
+            let pos = if by_word {
+                prev_word(content, selection.caret)
+            } else if !extend && !selection.is_empty() {
+                selection.start()
+            } else {
+                prev_char(content, selection.caret)
+            };
+            move_to(selection, pos);
+            false
+        },
+        Key::Named(NamedKey::ArrowRight) => {
+            let pos = if by_word {
+                next_word(content, selection.caret)
+            } else if !extend && !selection.is_empty() {
+                selection.end()
+            } else {
+                next_char(content, selection.caret)
+            };
+            move_to(selection, pos);
+            false
+        },
+        Key::Named(NamedKey::Home) => {
+            move_to(selection, 0);
+            false
+        },
+        Key::Named(NamedKey::End) => {
+            move_to(selection, content.len());
+            false
+        },
+        Key::Named(NamedKey::Backspace) => {
+            if selection.is_empty() {
+                selection.anchor = if by_word {
+                    prev_word(content, selection.caret)
+                } else {
+                    prev_char(content, selection.caret)
+                };
+            }
+            replace_selection(content, selection, "")
+        },
+        Key::Named(NamedKey::Delete) => {
+            if selection.is_empty() {
+                selection.anchor = if by_word {
+                    next_word(content, selection.caret)
+                } else {
+                    next_char(content, selection.caret)
+                };
+            }
+            replace_selection(content, selection, "")
+        },
+        Key::Character(c) if event.modifiers.control_key() && c.as_str() == "a" => {
+            *selection = Selection { anchor: 0, caret: content.len() };
+            false
+        },
+        _ => match event.text.as_deref() {
+            Some(text) if !event.modifiers.control_key()
+                && !text.is_empty()
+                && !text.chars().any(char::is_control) => replace_selection(content, selection, text),
+            _ => false,
+        },
+    }
+}
+
+fn replace_selection(content: &mut String, selection: &mut Selection, text: &str) -> bool {
+    if selection.is_empty() && text.is_empty() { return false }
+
+    let start = selection.start();
+    content.replace_range(start..selection.end(), text);
+    *selection = Selection::collapsed(start + text.len());
+    true
+}
+
+fn prev_char(content: &str, pos: usize) -> usize {
+    content[..pos]
+        .char_indices()
+        .next_back()
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+fn next_char(content: &str, pos: usize) -> usize {
+    content[pos..]
+        .chars()
+        .next()
+        .map(|c| pos + c.len_utf8())
+        .unwrap_or(pos)
+}
+
+/// Skip the separators, then the word before `pos`
+fn prev_word(content: &str, pos: usize) -> usize {
+    let mut chars = content[..pos]
+        .char_indices()
+        .rev()
+        .skip_while(|(_, c)| !c.is_alphanumeric())
+        .peekable();
+
+    let mut start = chars.peek().map(|(i, c)| i + c.len_utf8()).unwrap_or(0);
+    for (i, c) in chars {
+        if !c.is_alphanumeric() { break }
+        start = i;
+    }
+    start
+}
+
+/// Skip the separators, then the word after `pos`
+fn next_word(content: &str, pos: usize) -> usize {
+    content[pos..]
+        .char_indices()
+        .skip_while(|(_, c)| !c.is_alphanumeric())
+        .find(|(_, c)| !c.is_alphanumeric())
+        .map(|(i, _)| pos + i)
+        .unwrap_or(content.len())
+}
+
+#[cfg(test)]
+mod text_input_test {
+    use aplite_types::Rect;
+    use winit::keyboard::ModifiersState;
+    use super::*;
+
+    fn press(key: Key, text: Option<&str>, modifiers: ModifiersState) -> KeyEvent {
+        KeyEvent {
+            key,
+            text: text.map(ToString::to_string),
+            modifiers,
+            pressed: true,
+            repeat: false,
+        }
+    }
+
+    fn named(key: NamedKey, modifiers: ModifiersState) -> KeyEvent {
+        press(Key::Named(key), None, modifiers)
+    }
+
+    fn typed(text: &str) -> KeyEvent {
+        press(Key::Character(text.into()), Some(text), ModifiersState::empty())
+    }
+
+    #[test]
+    fn typing_updates_signal() {
+        aplite_future::Executor::init();
+
+        let value = Signal::new(String::from("ab"));
+        let id = text_input(value.clone()).id();
+
+        handle_key(id, &value, &typed("c"));
+        handle_key(id, &value, &named(NamedKey::ArrowLeft, ModifiersState::CONTROL));
+        handle_key(id, &value, &typed("x"));
+
+        assert_eq!(value.get_untracked(), "xabc");
+        VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            let text = tree.get(&id).unwrap().text.as_ref().unwrap();
+            assert_eq!(text.content, "xabc");
+            assert_eq!(text.selection, Some(Selection::collapsed(1)));
+        });
+    }
+
+    #[test]
+    fn long_value_scrolls_to_the_caret() {
+        aplite_future::Executor::init();
+
+        let font = aplite_renderer::Font::from_bytes(include_bytes!("../../crates/aplite_renderer/assets/DejaVuSans-Ascii.ttf"))
+            .expect("the test font should load");
+        let value = Signal::new(String::from("the quick brown fox jumps over the lazy dog"));
+        let id = text_input(value.clone())
+            .set_state(|state| state.set_font(font.clone()))
+            .id();
+
+        // the caret is at the end of the value, which is wider than the 184px content box
+        let visible = 200.0 - 16.0;
+        let (caret, scroll) = VIEW_STORAGE.with(|s| {
+            let tree = s.tree.borrow();
+            let state = tree.get(&id).unwrap();
+            assert_eq!(state.text_clip(), Some(Rect::new(8., 6., visible, 18.)));
+
+            let text = state.text.as_ref().unwrap();
+            (text.selection_rects().unwrap().1, text.scroll)
+        });
+        assert!(scroll > 0.0);
+        assert_eq!(caret.max_x() - scroll, visible);
+
+        handle_key(id, &value, &named(NamedKey::Home, ModifiersState::empty()));
+        let scroll = VIEW_STORAGE.with(|s| s.tree.borrow().get(&id).unwrap().text.as_ref().unwrap().scroll);
+        assert_eq!(scroll, 0.0);
+
+        // moving back to the end only scrolls by what is needed
+        handle_key(id, &value, &named(NamedKey::End, ModifiersState::empty()));
+        handle_key(id, &value, &named(NamedKey::ArrowLeft, ModifiersState::CONTROL));
+        let scroll_at_word = VIEW_STORAGE.with(|s| s.tree.borrow().get(&id).unwrap().text.as_ref().unwrap().scroll);
+        assert_eq!(scroll_at_word, caret.max_x() - visible);
+    }
+
+    #[test]
+    fn word_boundaries() {
+        let content = "hello, big world";
+        assert_eq!(prev_word(content, content.len()), 11);
+        assert_eq!(prev_word(content, 11), 7);
+        assert_eq!(prev_word(content, 7), 0);
+        assert_eq!(next_word(content, 0), 5);
+        assert_eq!(next_word(content, 5), 10);
+        assert_eq!(next_word(content, 10), content.len());
+    }
+
+    #[test]
+    fn editing() {
+        let none = ModifiersState::empty();
+        let mut content = String::new();
+        let mut selection = Selection::default();
+
+        for c in ["h", "é", "l", "l", "o"] {
+            assert!(edit(&mut content, &mut selection, &typed(c)));
+        }
+        assert_eq!(content, "héllo");
+        assert_eq!(selection, Selection::collapsed(content.len()));
+
+        // caret movement respects multibyte chars
+        edit(&mut content, &mut selection, &named(NamedKey::Home, none));
+        edit(&mut content, &mut selection, &named(NamedKey::ArrowRight, none));
+        edit(&mut content, &mut selection, &named(NamedKey::ArrowRight, none));
+        assert_eq!(selection.caret, 3);
+
+        assert!(edit(&mut content, &mut selection, &named(NamedKey::Backspace, none)));
+        assert_eq!(content, "hllo");
+
+        // shift extends the selection, typing replaces it
+        edit(&mut content, &mut selection, &named(NamedKey::End, ModifiersState::SHIFT));
+        assert_eq!(selection, Selection { anchor: 1, caret: 4 });
+        assert!(edit(&mut content, &mut selection, &typed("i")));
+        assert_eq!(content, "hi");
+
+        // ctrl + backspace deletes the word
+        edit(&mut content, &mut selection, &typed(" "));
+        edit(&mut content, &mut selection, &typed("there"));
+        assert!(edit(&mut content, &mut selection, &named(NamedKey::Backspace, ModifiersState::CONTROL)));
+        assert_eq!(content, "hi ");
+
+        edit(&mut content, &mut selection, &named(NamedKey::Home, none));
+        assert!(edit(&mut content, &mut selection, &named(NamedKey::Delete, none)));
+        assert_eq!(content, "i ");
+
+        // control chars are not inserted
+        assert!(!edit(&mut content, &mut selection, &press(Key::Named(NamedKey::Enter), Some("\r"), none)));
+        assert_eq!(content, "i ");
+    }
+}
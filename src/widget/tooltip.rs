@@ -0,0 +1,81 @@
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+
+use aplite_renderer::Shape;
+use aplite_types::{CornerRadius, Rgba};
+
+use crate::context::layout::Padding;
+use crate::view::{IntoView, View, ViewId};
+
+use super::{Text, WidgetExt};
+
+thread_local! {
+    /// The tooltip factories, the view is only created while the tooltip is shown
+    static TOOLTIPS: RefCell<HashMap<ViewId, Factory>> = RefCell::new(HashMap::new());
+}
+
+type Factory = Rc<dyn Fn() -> View>;
+
+pub(crate) const TOOLTIP_COLOR: Rgba<u8> = Rgba::new(30, 30, 30, 235);
+
+/// Types which can be used as the content of [`WidgetExt::tooltip`]:
+/// - `&'static str` & [`String`], shown as a plain label
+/// - `Fn() -> impl IntoView`, to build any view
+pub trait IntoTooltip<Args> {
+    fn into_tooltip(self) -> Rc<dyn Fn() -> View>;
+}
+
+impl IntoTooltip<&'static str> for &'static str {
+    fn into_tooltip(self) -> Rc<dyn Fn() -> View> {
+        Rc::new(move || label(self.to_string()))
+    }
+}
+
+impl IntoTooltip<String> for String {
+    fn into_tooltip(self) -> Rc<dyn Fn() -> View> {
+        Rc::new(move || label(self.clone()))
+    }
+}
+
+impl<F, IV> IntoTooltip<View> for F
+where
+    F: Fn() -> IV + 'static,
+    IV: IntoView + 'static,
+{
+    fn into_tooltip(self) -> Rc<dyn Fn() -> View> {
+        Rc::new(move || self().into_view())
+    }
+}
+
+fn label(content: String) -> View {
+    Text::new(move |_| content.clone())
+        .set_state(|state| {
+            state.set_name("Tooltip");
+            state.set_padding(Padding::new(4.0, 4.0, 8.0, 8.0));
+            state.set_background(TOOLTIP_COLOR);
+            state.set_shape(Shape::RoundedRect);
+            state.set_corner_radius(CornerRadius::splat(15.0));
+        })
+        .into_view()
+}
+
+pub(super) fn register_tooltip(id: ViewId, factory: Factory) {
+    TOOLTIPS.with(|t| t.borrow_mut().insert(id, factory));
+}
+
+pub(crate) fn has_tooltip(id: &ViewId) -> bool {
+    TOOLTIPS.with(|t| t.borrow().contains_key(id))
+}
+
+/// Build a new view of the tooltip of the widget, if it has one
+pub(crate) fn create_tooltip(id: &ViewId) -> Option<View> {
+    // cloned out, because the factory may register the tooltips of the new views
+    let factory = TOOLTIPS.with(|t| t.borrow().get(id).cloned());
+    factory.map(|factory| factory())
+}
+
+/// Forget the tooltips of the removed views
+pub(crate) fn remove_tooltips(removed: &HashSet<ViewId>) {
+    TOOLTIPS.with(|t| t.borrow_mut().retain(|id, _| !removed.contains(id)));
+}